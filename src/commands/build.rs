@@ -1,55 +1,58 @@
-use crate::projects;
-use crate::tasks;
+use crate::projects::{
+    self,
+    cargo::{cargo_metadata, detect_workspace_members},
+    is_custom_type, is_environment_type, is_native_type, is_node_type, is_python_type,
+    is_runner_type, make, zig,
+};
+use crate::tasks::{self, PipelineKind};
 use std::time::Instant;
+use tokio::fs::metadata;
 
-pub async fn run() {
+pub async fn run(package: Option<String>) -> bool {
     println!();
 
     // Get project information
     let project = projects::get_or_create_project().await;
+    if !select_package(&project, package.as_deref()).await {
+        return false;
+    }
 
-    if project.is_rust_project || project.is_uv_project || project.is_fortran_project {
-        build_project(&project).await;
+    if let Some(pipeline) = tasks::resolve_pipeline(&project, PipelineKind::Build).await {
+        build_project(&project, &pipeline).await
+    } else if is_runner_type(&project.project_type) {
+        missing_target(&project.project_type, &make::BUILD_TARGETS)
+    } else if is_custom_type(&project.project_type) {
+        missing_setting("`[project.build].release`")
     } else {
         println!("[TIP] + Unknown project type. No build configuration found.");
         println!("[TIP] + [Task End]");
         println!();
+        false
     }
 }
 
-async fn build_project(project: &projects::Project) {
-    if project.is_rust_project {
-        build_rust_project(project).await;
-    } else if project.is_uv_project {
-        build_uv_project(project).await;
-    } else if project.is_fortran_project {
-        build_fortran_project(project).await;
+async fn build_project(project: &projects::Project, pipeline: &projects::Pipeline) -> bool {
+    if is_python_type(&project.project_type) {
+        println!("[TIP] + Build the project.");
+    } else {
+        println!("[TIP] + Build for Release.");
     }
-}
-
-async fn build_rust_project(_project: &projects::Project) {
-    println!("[TIP] + Build for Release.");
     println!();
 
     // Start timer for all tasks
     let start_time = Instant::now();
 
-    println!("[1/3] + Download dependencies");
-
-    // Execute tasks using the new task system
-    tasks::execute_task_by_id(tasks::CARGO_UPDATE).await;
-    tasks::execute_task_by_id(tasks::CARGO_FMT).await;
-
-    println!();
-    println!("[2/3] + Check the project");
-    tasks::execute_task_by_id(tasks::CARGO_CHECK).await;
-
-    println!();
-    println!("[3/3] + Build the project");
-    tasks::execute_task_by_id(tasks::CARGO_BUILD_RELEASE).await;
+    // Execute the stages using the task system, stopping at the first failure
+    let result = tasks::run_stages(&pipeline.stages, pipeline.stages.len()).await;
 
-    println!();
-    println!("[TIP] + Build at + `target` .");
+    match &result {
+        Ok(()) => {
+            if let Some(output_dir) = output_dir(project).await {
+                println!("[TIP] + Build at + `{}` .", output_dir);
+            }
+        }
+        Err(failure) => failure.report(),
+    }
 
     // Calculate and display total elapsed time
     let elapsed = start_time.elapsed();
@@ -58,72 +61,96 @@ async fn build_rust_project(_project: &projects::Project) {
 
     println!("[TIP] + [Task End]");
     println!();
+    result.is_ok()
 }
 
-async fn build_uv_project(_project: &projects::Project) {
-    println!("[TIP] + Build the project.");
-    println!();
-
-    // Start timer for all tasks
-    let start_time = Instant::now();
-
-    println!("[1/3] + Lock the project dependencies");
-
-    // Execute tasks using the new task system
-    tasks::execute_task_by_id(tasks::UV_LOCK).await;
-
-    println!();
-    println!("[2/3] + Check and Format the project");
-    tasks::execute_task_by_id(tasks::UV_RUFF_CHECK).await;
-    tasks::execute_task_by_id(tasks::UV_RUFF_FORMAT).await;
-
-    println!();
-    println!("[3/3] + Build the project");
-    tasks::execute_task_by_id(tasks::UV_BUILD).await;
-
-    println!();
-    println!("[TIP] + Build at + `dist` .");
-
-    // Calculate and display total elapsed time
-    let elapsed = start_time.elapsed();
-    let elapsed_seconds = elapsed.as_secs_f64();
-    println!("[TIP] + Done the tasks in {:.2}s.", elapsed_seconds);
+// Get the directory the build output goes to
+pub async fn output_dir(project: &projects::Project) -> Option<String> {
+    if is_environment_type(&project.project_type) {
+        // The project inside the environment decides, fpm or a Python package
+        if metadata("fpm.toml").await.is_ok() {
+            Some(String::from("build"))
+        } else {
+            Some(String::from("dist"))
+        }
+    } else if is_python_type(&project.project_type) {
+        Some(String::from("dist"))
+    } else if is_node_type(&project.project_type) {
+        // Bundlers write to `dist`, Create React App to `build`
+        for dir in ["dist", "build"] {
+            if metadata(dir).await.is_ok() {
+                return Some(dir.to_string());
+            }
+        }
+        None
+    } else if project.project_type == "fpm" {
+        Some(String::from("build"))
+    } else if project.project_type == "zig" {
+        Some(String::from(zig::OUT_DIR))
+    } else if project.is_rust_project {
+        // cargo knows about `CARGO_TARGET_DIR` and `[build] target-dir`
+        match cargo_metadata().await {
+            Some(cargo) => Some(cargo.target_dir()),
+            None => Some(String::from("target")),
+        }
+    } else if project.is_fortran_project || is_native_type(&project.project_type) {
+        Some(String::from("target"))
+    } else {
+        None
+    }
+}
 
+// Tell which targets a Makefile or justfile project lacks for a command
+pub fn missing_target(project_type: &str, names: &[&str]) -> bool {
+    let names: Vec<String> = names.iter().map(|name| format!("`{}`", name)).collect();
+    println!(
+        "[TIP] + No {} target in the {}.",
+        names.join(" or "),
+        make::runner_file_name(project_type)
+    );
     println!("[TIP] + [Task End]");
     println!();
+    false
 }
 
-async fn build_fortran_project(project: &projects::Project) {
-    println!("[TIP] + Build for Release.");
+// Tell which setting of lox.toml a custom project lacks for a command
+pub fn missing_setting(setting: &str) -> bool {
+    println!("[TIP] + Nothing to do, set {} in lox.toml.", setting);
+    println!("[TIP] + [Task End]");
     println!();
+    false
+}
 
-    // Start timer for all tasks
-    let start_time = Instant::now();
-
-    println!("[1/1] + Build the project");
+// Check the package picked with `-p`, and limit the cargo tasks to it
+pub async fn select_package(project: &projects::Project, package: Option<&str>) -> bool {
+    let Some(package) = package else {
+        return true;
+    };
 
-    // Execute the appropriate Fortran build task based on project type
-    if project.project_type == "fpm" {
-        tasks::execute_task_by_id(tasks::FPM_BUILD_RELEASE).await;
-    } else {
-        tasks::execute_task_by_id(tasks::FLANG_BUILD_RELEASE).await;
+    if !project.is_rust_project {
+        println!("[TIP] + `-p` only applies to cargo projects.");
+        println!("[TIP] + [Task End]");
+        println!();
+        return false;
     }
 
-    println!();
-    println!(
-        "[TIP] + Build at + `{}` .",
-        if project.project_type == "fpm" {
-            "build"
-        } else {
-            "target"
+    let members = detect_workspace_members().await;
+    let known = if members.is_empty() {
+        package == project.name
+    } else {
+        members.iter().any(|member| member.name == package)
+    };
+    if !known {
+        eprintln!("[ERROR] + `{}` is not a package of the project.", package);
+        if !members.is_empty() {
+            let names: Vec<&str> = members.iter().map(|member| member.name.as_str()).collect();
+            println!("[TIP] + Workspace members: {}.", names.join(", "));
         }
-    );
-
-    // Calculate and display total elapsed time
-    let elapsed = start_time.elapsed();
-    let elapsed_seconds = elapsed.as_secs_f64();
-    println!("[TIP] + Done the tasks in {:.2}s.", elapsed_seconds);
+        println!("[TIP] + [Task End]");
+        println!();
+        return false;
+    }
 
-    println!("[TIP] + [Task End]");
-    println!();
+    tasks::set_cargo_package(package);
+    true
 }
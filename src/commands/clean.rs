@@ -0,0 +1,341 @@
+use crate::commands::build::{missing_setting, missing_target};
+use crate::projects::{
+    self, UserTask, cargo::cargo_metadata, custom, is_custom_type, is_environment_type,
+    is_native_type, is_node_type, is_runner_type, make,
+};
+use crate::tasks::{self, StepFailure};
+use std::env::current_dir;
+use std::fs::{read_dir, remove_dir_all, remove_file, symlink_metadata};
+use std::io;
+use std::path::{Path, PathBuf};
+use std::time::Instant;
+
+// Which build outputs `lox clean` removes
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum CleanMode {
+    // The dev and release outputs
+    Build,
+    Dev,
+    Release,
+    // Every output, cache and environment lox knows of
+    All,
+}
+
+pub async fn run(mode: CleanMode, dry_run: bool) -> bool {
+    println!();
+
+    // Get project information
+    let project = projects::get_or_create_project().await;
+
+    // A Makefile or justfile knows its own outputs, its `clean` target removes them, and a custom
+    // project cleans with its `[tasks.clean]` table
+    let is_runner = is_runner_type(&project.project_type);
+    if is_runner || is_custom_type(&project.project_type) {
+        let name = if is_runner {
+            make::CLEAN_TARGET
+        } else {
+            custom::CLEAN_TASK
+        };
+        return match project.user_tasks.iter().find(|task| task.name == name) {
+            Some(task) => clean_with_task(task, dry_run).await,
+            None if is_runner => missing_target(&project.project_type, &[name]),
+            None => missing_setting(&format!("`[tasks.{}]`", name)),
+        };
+    }
+
+    let Some(paths) = artifact_paths(&project, mode).await else {
+        println!("[TIP] + Unknown project type. No clean configuration found.");
+        println!("[TIP] + [Task End]");
+        println!();
+        return false;
+    };
+
+    // A shared `CARGO_TARGET_DIR` or `[build] target-dir` holds the builds of other projects too
+    let root = current_dir().unwrap_or_else(|_| PathBuf::from("."));
+    if let Some(outside) = paths
+        .iter()
+        .find(|path| !is_inside_project(Path::new(path), &root))
+    {
+        println!(
+            "[TIP] + `{}` is outside of the project, other projects may build there too.",
+            outside
+        );
+        if project.is_rust_project {
+            println!("[TIP] + Run `cargo clean` to clean it.");
+        }
+        println!("[TIP] + [Task End]");
+        println!();
+        return false;
+    }
+    clean_project(&paths, dry_run)
+}
+
+// Whether a path is inside of the project root, symlinks included
+fn is_inside_project(path: &Path, root: &Path) -> bool {
+    let root = root.canonicalize().unwrap_or_else(|_| root.to_path_buf());
+    let path = if path.is_absolute() {
+        path.to_path_buf()
+    } else {
+        root.join(path)
+    };
+    // A missing path can't be resolved, but has nothing to remove either
+    let path = path.canonicalize().unwrap_or(path);
+    path.starts_with(&root) && path != root
+}
+
+// Get the directories a project type writes its outputs to
+async fn artifact_paths(project: &projects::Project, mode: CleanMode) -> Option<Vec<String>> {
+    if project.is_rust_project {
+        // cargo knows about `CARGO_TARGET_DIR` and `[build] target-dir`
+        let target = match cargo_metadata().await {
+            Some(cargo) => cargo.target_dir(),
+            None => String::from("target"),
+        };
+        let path = match mode {
+            CleanMode::Dev => format!("{}/debug", target),
+            CleanMode::Release => format!("{}/release", target),
+            CleanMode::Build | CleanMode::All => target,
+        };
+        return Some(vec![path]);
+    }
+
+    let paths = if project.project_type == "fpm" {
+        // fpm keeps every profile under `build`, in directories named by a hash
+        vec!["build"]
+    } else if project.project_type == "zig" {
+        // Both profiles install to `zig-out`, the compiler caches go to `.zig-cache`
+        match mode {
+            CleanMode::Dev | CleanMode::Release | CleanMode::Build => vec!["zig-out"],
+            CleanMode::All => vec!["zig-out", ".zig-cache"],
+        }
+    } else if is_native_type(&project.project_type) {
+        match mode {
+            CleanMode::Dev => vec!["target/dev"],
+            CleanMode::Release => vec!["target/release"],
+            CleanMode::Build | CleanMode::All => vec!["target"],
+        }
+    } else if project.is_fortran_project {
+        match mode {
+            CleanMode::Dev => vec!["target/dev", "target/test"],
+            CleanMode::Release => vec!["target/release"],
+            CleanMode::Build | CleanMode::All => vec!["target"],
+        }
+    } else if matches!(project.project_type.as_str(), "uv" | "poetry" | "pdm") {
+        match mode {
+            CleanMode::Dev => vec![".venv"],
+            CleanMode::Release | CleanMode::Build => vec!["dist"],
+            CleanMode::All => vec!["dist", ".venv", ".pytest_cache", ".ruff_cache"],
+        }
+    } else if is_environment_type(&project.project_type) {
+        // conda keeps its environments outside of the project, pixi keeps them in `.pixi`
+        match mode {
+            CleanMode::Dev | CleanMode::Release | CleanMode::Build => vec!["build", "dist"],
+            CleanMode::All => vec!["build", "dist", ".pixi"],
+        }
+    } else if is_node_type(&project.project_type) {
+        match mode {
+            CleanMode::Dev | CleanMode::Release | CleanMode::Build => vec!["dist", "build"],
+            CleanMode::All => vec!["dist", "build", "node_modules"],
+        }
+    } else if project.project_type == "pip" {
+        // setuptools builds the wheel in `build` before copying it to `dist`
+        match mode {
+            CleanMode::Dev => vec![".venv"],
+            CleanMode::Release | CleanMode::Build => vec!["dist", "build"],
+            CleanMode::All => vec!["dist", "build", ".venv", ".pytest_cache"],
+        }
+    } else {
+        return None;
+    };
+    Some(paths.into_iter().map(String::from).collect())
+}
+
+fn clean_project(paths: &[String], dry_run: bool) -> bool {
+    if dry_run {
+        println!("[TIP] + Clean the project (dry run, nothing is deleted).");
+    } else {
+        println!("[TIP] + Clean the project.");
+    }
+    println!();
+
+    // Start timer for all tasks
+    let start_time = Instant::now();
+
+    let existing: Vec<&str> = paths
+        .iter()
+        .map(String::as_str)
+        .filter(|path| symlink_metadata(path).is_ok())
+        .collect();
+
+    let mut freed = 0;
+    let mut success = true;
+    if existing.is_empty() {
+        println!("[TIP] + Nothing to clean.");
+    } else {
+        println!("[1/1] + Remove the build outputs");
+        for path in existing {
+            let size = disk_usage(Path::new(path));
+            let status = if dry_run {
+                freed += size;
+                "Skipped".to_string()
+            } else {
+                match remove_path(Path::new(path)) {
+                    Ok(()) => {
+                        freed += size;
+                        "Done".to_string()
+                    }
+                    Err(e) => {
+                        success = false;
+                        format!("Failed: {}", e)
+                    }
+                }
+            };
+            println!("  - Clean | {} | {} | {}.", path, format_size(size), status);
+        }
+        println!();
+
+        if dry_run {
+            println!("[TIP] + Would free {}.", format_size(freed));
+        } else {
+            println!("[TIP] + Freed {}.", format_size(freed));
+        }
+    }
+
+    // Calculate and display total elapsed time
+    let elapsed = start_time.elapsed();
+    let elapsed_seconds = elapsed.as_secs_f64();
+    println!("[TIP] + Done the tasks in {:.2}s.", elapsed_seconds);
+
+    println!("[TIP] + [Task End]");
+    println!();
+    success
+}
+
+// Run the `clean` task of a Makefile, justfile or custom project, the same for every mode
+async fn clean_with_task(task: &UserTask, dry_run: bool) -> bool {
+    if dry_run {
+        println!("[TIP] + Clean the project (dry run, nothing is deleted).");
+    } else {
+        println!("[TIP] + Clean the project.");
+    }
+    println!();
+
+    // Start timer for all tasks
+    let start_time = Instant::now();
+
+    println!("[1/1] + Run the clean task");
+    let result = if dry_run {
+        let words = std::iter::once(&task.cmd).chain(&task.args);
+        let display_name = shlex::try_join(words.clone().map(String::as_str))
+            .unwrap_or_else(|_| words.cloned().collect::<Vec<_>>().join(" "));
+        println!("  - Task | {} | Skipped.", display_name);
+        Ok(())
+    } else {
+        tasks::execute_tasks(std::slice::from_ref(&task.name)).await
+    };
+    println!();
+
+    if let Err(failed_tasks) = &result {
+        StepFailure::new(1, 1, "Run the clean task", failed_tasks.clone()).report();
+    }
+
+    // Calculate and display total elapsed time
+    let elapsed = start_time.elapsed();
+    let elapsed_seconds = elapsed.as_secs_f64();
+    println!("[TIP] + Done the tasks in {:.2}s.", elapsed_seconds);
+
+    println!("[TIP] + [Task End]");
+    println!();
+    result.is_ok()
+}
+
+// Get the size of a file, or of everything in a directory, without following symlinks
+fn disk_usage(path: &Path) -> u64 {
+    let Ok(metadata) = symlink_metadata(path) else {
+        return 0;
+    };
+    if !metadata.is_dir() {
+        return metadata.len();
+    }
+
+    read_dir(path)
+        .map(|entries| {
+            entries
+                .flatten()
+                .map(|entry| disk_usage(&entry.path()))
+                .sum()
+        })
+        .unwrap_or(0)
+}
+
+// Remove a file or a directory with everything in it
+fn remove_path(path: &Path) -> io::Result<()> {
+    if symlink_metadata(path)?.is_dir() {
+        remove_dir_all(path)
+    } else {
+        remove_file(path)
+    }
+}
+
+// Format a number of bytes for display
+fn format_size(bytes: u64) -> String {
+    const UNITS: [&str; 4] = ["KiB", "MiB", "GiB", "TiB"];
+
+    if bytes < 1024 {
+        return format!("{} B", bytes);
+    }
+    let mut size = bytes as f64 / 1024.0;
+    let mut unit = 0;
+    while size >= 1024.0 && unit < UNITS.len() - 1 {
+        size /= 1024.0;
+        unit += 1;
+    }
+    format!("{:.2} {}", size, UNITS[unit])
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs::create_dir_all;
+
+    // A project next to a shared target directory, in a directory of its own
+    fn layout(name: &str) -> (PathBuf, PathBuf) {
+        let base = std::env::temp_dir().join(format!("lox-clean-{}-{}", name, std::process::id()));
+        let project = base.join("project");
+        let shared = base.join("shared");
+        create_dir_all(project.join("target/debug")).unwrap();
+        create_dir_all(shared.join("debug")).unwrap();
+        (project, shared)
+    }
+
+    #[test]
+    fn outputs_of_the_project_are_inside() {
+        let (project, shared) = layout("inside");
+        assert!(is_inside_project(Path::new("target"), &project));
+        assert!(is_inside_project(Path::new("./target/debug"), &project));
+        assert!(is_inside_project(&project.join("target"), &project));
+        assert!(is_inside_project(Path::new("dist"), &project));
+        let _ = remove_dir_all(shared.parent().unwrap());
+    }
+
+    #[test]
+    fn shared_target_directory_is_outside() {
+        let (project, shared) = layout("outside");
+        assert!(!is_inside_project(&shared, &project));
+        assert!(!is_inside_project(&shared.join("debug"), &project));
+        assert!(!is_inside_project(Path::new("../shared"), &project));
+        assert!(!is_inside_project(Path::new("."), &project));
+        assert!(!is_inside_project(&project, &project));
+        let _ = remove_dir_all(shared.parent().unwrap());
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn symlink_to_a_shared_target_directory_is_outside() {
+        let (project, shared) = layout("symlink");
+        std::os::unix::fs::symlink(&shared, project.join("linked")).unwrap();
+        assert!(!is_inside_project(Path::new("linked"), &project));
+        assert!(!is_inside_project(Path::new("linked/debug"), &project));
+        let _ = remove_dir_all(shared.parent().unwrap());
+    }
+}
@@ -1,144 +1,143 @@
-use crate::projects;
-use std::fs::metadata;
+use crate::commands::build::select_package;
+use crate::commands::run::{
+    build_example, build_first, needs_build, resolve_binary, resolve_custom_command,
+    resolve_environment_command, resolve_node_script, resolve_script, resolve_target_command,
+    run_command,
+};
+use crate::projects::{
+    self, Stage, is_custom_type, is_environment_type, is_native_type, is_node_type, is_python_type,
+    is_runner_type,
+};
+use crate::tasks;
 use std::time::Instant;
-use tokio::process::Command;
 
-pub async fn run() {
+pub async fn run(
+    package: Option<String>,
+    binary: Option<String>,
+    example: Option<String>,
+    args: Vec<String>,
+) -> bool {
     println!();
 
     // Get project information
     let project = projects::get_or_create_project().await;
+    if !select_package(&project, package.as_deref()).await {
+        return false;
+    }
 
-    if project.is_rust_project || project.is_fortran_project {
-        // Check if it's a library project
-        if project.is_library {
-            println!(
-                "[TIP] + The current project is a library(lib) project, which doesn't have binary output."
-            );
-            println!("[TIP] + [Task End]");
-            println!();
-            return;
-        }
-        run_project(&project).await;
-    } else if project.is_uv_project {
-        println!("[TIP] + The `dash` command is not supported for `uv` projects.");
-        println!("[TIP] + Please use `lox run` or `lox build`.");
-        println!("[TIP] + [Task End]");
-        println!();
+    if project.is_rust_project
+        || project.is_fortran_project
+        || is_native_type(&project.project_type)
+    {
+        let Some(target_debug) = resolve_binary(
+            &project,
+            package.as_deref(),
+            binary.as_deref(),
+            example.as_deref(),
+            "debug",
+            &project.run_commands.dev,
+        )
+        .await
+        else {
+            return false;
+        };
+        run_project(
+            &project,
+            &target_debug,
+            package.as_deref(),
+            example.as_deref(),
+            &args,
+        )
+        .await
+    } else if is_python_type(&project.project_type) {
+        let Some(command) = resolve_script(&project, None, &project.run_commands.dev).await else {
+            return false;
+        };
+        run_project(&project, &command, None, None, &args).await
+    } else if is_node_type(&project.project_type) {
+        let Some(command) = resolve_node_script(&project, None, &project.run_commands.dev).await
+        else {
+            return false;
+        };
+        run_project(&project, &command, None, None, &args).await
+    } else if is_environment_type(&project.project_type) {
+        let Some(command) = resolve_environment_command(&project.run_commands.dev) else {
+            return false;
+        };
+        run_project(&project, &command, None, None, &args).await
+    } else if is_runner_type(&project.project_type) {
+        let Some(command) = resolve_target_command(&project, &project.run_commands.dev) else {
+            return false;
+        };
+        run_project(&project, &command, None, None, &args).await
+    } else if is_custom_type(&project.project_type) {
+        let Some(command) = resolve_custom_command(&project.run_commands.dev, "dev") else {
+            return false;
+        };
+        run_project(&project, &command, None, None, &args).await
     } else {
         println!("[TIP] + Unknown project type. No dash configuration found.");
         println!("[TIP] + [Task End]");
         println!();
+        false
     }
 }
 
-async fn run_project(project: &projects::Project) {
-    let target_debug = &project.run_commands.dev;
-
+async fn run_project(
+    project: &projects::Project,
+    target_debug: &str,
+    package: Option<&str>,
+    example: Option<&str>,
+    args: &[String],
+) -> bool {
     // Start timer for all tasks
     let overall_start_time = Instant::now();
 
-    // Check if binary exists for Rust or Fortran projects
-    if project.is_rust_project || project.is_fortran_project {
-        // Determine the target directory based on project type
-        let is_fpm = project.project_type == "fpm";
-        let target_dir = if is_fpm { "./build" } else { "./target" };
-        let target_msg = if is_fpm { "build" } else { "target" };
-
-        match metadata(target_dir) {
-            Ok(_) => {
-                // For FPM, we don't check the exact binary path since it's managed by FPM
-                if !is_fpm && !metadata(target_debug).is_ok() {
-                    println!("[TIP] + Nothing at `{}` .", target_msg);
-                    println!();
-                    println!("[1/2] + Build the project first.");
-
-                    // Run lox dev
-                    println!("  - Task | lox dev | ");
-                    let dev_status = Command::new("cargo")
-                        .arg("run")
-                        .arg("--")
-                        .arg("dev")
-                        .status()
-                        .await
-                        .expect("Failed to execute lox dev");
-                    println!(
-                        "  - Task | lox dev | {}.",
-                        if dev_status.success() {
-                            "Done"
-                        } else {
-                            "Failed"
-                        }
-                    );
-
-                    println!();
-                }
-            }
-            Err(_) => {
-                println!("[TIP] + Nothing at `{}` .", target_msg);
-                println!();
-                println!("[1/2] + Build the project first.");
-
-                // Run lox dev
-                println!("  - Task | lox dev | ");
-                let dev_status = Command::new("cargo")
-                    .arg("run")
-                    .arg("--")
-                    .arg("dev")
-                    .status()
-                    .await
-                    .expect("Failed to execute lox dev");
-                println!(
-                    "  - Task | lox dev | {}.",
-                    if dev_status.success() {
-                        "Done"
-                    } else {
-                        "Failed"
-                    }
-                );
-
-                println!();
-            }
-        }
-    }
-
-    println!("[2/2] + Run the project.");
-
-    // Run the debug command and measure its time
-    println!("  - Task | {} | ", target_debug);
-    let command_start_time = Instant::now();
-
-    // Split command into binary and arguments for proper execution
-    let mut parts = target_debug.split_whitespace();
-    let run_status = if let Some(binary) = parts.next() {
-        let args: Vec<&str> = parts.collect();
-        Command::new(binary)
-            .args(args)
-            .status()
-            .await
-            .expect(format!("Failed to execute {}", target_debug).as_str())
+    // Prepare the environment of Python, conda and pixi projects, install the dependencies of
+    // Node.js packages, and for native projects check if the binary exists, and build it in dev
+    // mode if not. The `run` target of a Makefile or justfile builds what it needs itself, and
+    // custom projects run their command as it is
+    let prepared = if is_python_type(&project.project_type)
+        || is_environment_type(&project.project_type)
+        || is_node_type(&project.project_type)
+    {
+        let (name, task) = match project.project_type.as_str() {
+            "uv" => ("Sync the environment", tasks::UV_SYNC_DEV),
+            "conda" => ("Create the environment", tasks::CONDA_ENV),
+            "pixi" => ("Create the environment", tasks::PIXI_INSTALL),
+            "poetry" => ("Install the dependencies", tasks::POETRY_INSTALL),
+            "pdm" => ("Install the dependencies", tasks::PDM_INSTALL),
+            "npm" | "pnpm" | "yarn" => ("Install the dependencies", tasks::NODE_INSTALL),
+            _ => ("Install the dependencies", tasks::PIP_INSTALL),
+        };
+        let prepare = Stage {
+            name: name.to_string(),
+            tasks: vec![task.to_string()],
+            continue_on_error: false,
+        };
+        tasks::run_stages(&[prepare], 2).await
+    } else if is_runner_type(&project.project_type)
+        || is_custom_type(&project.project_type)
+        || !needs_build(project, target_debug)
+    {
+        Ok(())
+    } else if let Some(example) = example {
+        build_example(example, package, "debug", 2).await
     } else {
-        panic!("Empty command string")
+        build_first(project, "dev", package, 2).await
     };
 
-    let command_elapsed = command_start_time.elapsed();
-    let command_elapsed_seconds = command_elapsed.as_secs_f64();
-
-    println!(
-        "  - Task | {} | {}.",
-        target_debug,
-        if run_status.success() {
-            "Done"
-        } else {
-            "Failed"
+    let result = match prepared {
+        Ok(()) => {
+            println!("[2/2] + Run the project.");
+            run_command(target_debug, args, 2).await
         }
-    );
+        Err(failure) => Err(failure),
+    };
 
-    println!();
-    println!(
-        "[TIP] + Run the project in {:.2}s.",
-        command_elapsed_seconds
-    );
+    if let Err(failure) = &result {
+        failure.report();
+    }
 
     // Calculate and display total elapsed time for all tasks
     let overall_elapsed = overall_start_time.elapsed();
@@ -147,4 +146,5 @@ async fn run_project(project: &projects::Project) {
 
     println!("[TIP] + [Task End]");
     println!();
+    result.is_ok()
 }
@@ -1,98 +1,66 @@
-use crate::projects;
-use crate::tasks;
+use crate::commands::build::{missing_setting, missing_target, output_dir, select_package};
+use crate::projects::{
+    self, conda, is_custom_type, is_environment_type, is_python_type, is_runner_type, make,
+    python::VENV_DIR,
+};
+use crate::tasks::{self, PipelineKind};
 use std::time::Instant;
+use tokio::fs::metadata;
 
-pub async fn run() {
+pub async fn run(package: Option<String>) -> bool {
     println!();
 
     // Get project information
     let project = projects::get_or_create_project().await;
+    if !select_package(&project, package.as_deref()).await {
+        return false;
+    }
 
-    if project.is_rust_project {
-        build_dev_rust_project(&project).await;
-    } else if project.is_uv_project {
-        println!("[TIP] + The `dev` command is not supported for `uv` projects.");
-        println!("[TIP] + Please use `lox run` or `lox build`.");
-        println!("[TIP] + [Task End]");
-        println!();
-    } else if project.is_fortran_project {
-        build_dev_fortran_project(&project).await;
+    if let Some(pipeline) = tasks::resolve_pipeline(&project, PipelineKind::Dev).await {
+        build_dev_project(&project, &pipeline).await
+    } else if is_runner_type(&project.project_type) {
+        missing_target(&project.project_type, &make::BUILD_TARGETS)
+    } else if is_custom_type(&project.project_type) {
+        missing_setting("`[project.build].dev`")
     } else {
         println!("[TIP] + Unknown project type. No dev configuration found.");
         println!("[TIP] + [Task End]");
         println!();
+        false
     }
 }
 
-async fn build_dev_rust_project(_project: &projects::Project) {
+async fn build_dev_project(project: &projects::Project, pipeline: &projects::Pipeline) -> bool {
     println!("[TIP] + Build for Dev.");
     println!();
 
     // Start timer for all tasks
     let start_time = Instant::now();
 
-    println!("[1/3] + Download dependencies");
+    // Execute the stages using the task system, stopping at the first failure
+    let result = tasks::run_stages(&pipeline.stages, pipeline.stages.len()).await;
 
-    // Execute tasks using the new task system
-    tasks::execute_task_by_id(tasks::CARGO_UPDATE).await;
-    tasks::execute_task_by_id(tasks::CARGO_FMT).await;
-
-    println!();
-    println!("[2/3] + Check the project");
-    tasks::execute_task_by_id(tasks::CARGO_CHECK).await;
-
-    println!();
-    println!("[3/3] + Build the project");
-    tasks::execute_task_by_id(tasks::CARGO_BUILD).await;
-
-    println!();
-    println!("[TIP] + Build at + `target` .");
-
-    // Calculate and display total elapsed time
-    let elapsed = start_time.elapsed();
-    let elapsed_seconds = elapsed.as_secs_f64();
-    println!("[TIP] + Done the tasks in {:.2}s.", elapsed_seconds);
-
-    println!("[TIP] + [Task End]");
-    println!();
-}
-
-async fn build_dev_fortran_project(project: &projects::Project) {
-    println!("[TIP] + Build for Dev.");
-    println!();
-
-    // Start timer for all tasks
-    let start_time = Instant::now();
-
-    println!("[1/1] + Build the project");
-
-    // Execute the appropriate Fortran dev build task based on project type
-    if project.project_type == "fpm" {
-        // For FPM, we need to use the fpm build command directly since we don't have a FLANG_BUILD_DEV equivalent
-        println!("  - Task | fpm build | ");
-        let status = tokio::process::Command::new("fpm")
-            .arg("build")
-            .status()
-            .await
-            .expect("Failed to execute fpm build");
-        println!(
-            "  - Task | fpm build | {}.",
-            if status.success() { "Done" } else { "Failed" }
-        );
-    } else {
-        // Execute the Fortran dev build task using the task system
-        tasks::execute_task_by_id(tasks::FLANG_BUILD_DEV).await;
-    }
-
-    println!();
-    println!(
-        "[TIP] + Build at + `{}` .",
-        if project.project_type == "fpm" {
-            "build"
-        } else {
-            "target"
+    match &result {
+        // Python projects are not built in dev mode, only installed into their environment,
+        // which Poetry may keep outside of the project
+        Ok(()) if is_python_type(&project.project_type) => {
+            if metadata(VENV_DIR).await.is_ok() {
+                println!("[TIP] + Environment at + `{}` .", VENV_DIR);
+            }
+        }
+        // conda keeps its environments outside of the project, pixi in `.pixi`
+        Ok(()) if is_environment_type(&project.project_type) => {
+            if let Some(location) = conda::environment_location(&project.project_type).await {
+                println!("[TIP] + Environment at + `{}` .", location);
+            }
         }
-    );
+        Ok(()) => {
+            if let Some(output_dir) = output_dir(project).await {
+                println!("[TIP] + Build at + `{}` .", output_dir);
+            }
+        }
+        Err(failure) => failure.report(),
+    }
 
     // Calculate and display total elapsed time
     let elapsed = start_time.elapsed();
@@ -101,4 +69,5 @@ async fn build_dev_fortran_project(project: &projects::Project) {
 
     println!("[TIP] + [Task End]");
     println!();
+    result.is_ok()
 }
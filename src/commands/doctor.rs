@@ -1,19 +1,18 @@
-use crate::projects;
+use crate::projects::{self, conda};
 use std::env;
-use std::fs::write;
 use std::time::Instant;
 use tokio::fs::metadata;
 use tokio::process::Command;
 
-pub async fn run(quiet: bool) {
+pub async fn run(quiet: bool) -> bool {
     // Start timer for all tasks
     let start_time = Instant::now();
 
     // Check if this is the first run by looking for the config file
-    let is_first_run = !metadata("lox.toml").await.is_ok();
+    let is_first_run = metadata("lox.toml").await.is_err();
 
-    // Get project information from the shared module
-    let project = projects::detect_project_info().await;
+    // Get project information from the shared module, the same one every command uses
+    let project = projects::get_or_create_project().await;
 
     // Get Rust-specific information for Rust projects
     let (rustc_version, cargo_version) = if project.is_rust_project {
@@ -25,7 +24,6 @@ pub async fn run(quiet: bool) {
             .expect("Failed to execute rustc command");
         let rustc_version_str = String::from_utf8_lossy(&rustc_output.stdout);
         let rustc_version = rustc_version_str
-            .trim()
             .split_whitespace()
             .nth(1)
             .unwrap_or("unknown")
@@ -39,7 +37,6 @@ pub async fn run(quiet: bool) {
             .expect("Failed to execute cargo command");
         let cargo_version_str = String::from_utf8_lossy(&cargo_output.stdout);
         let cargo_version = cargo_version_str
-            .trim()
             .split_whitespace()
             .nth(1)
             .unwrap_or("unknown")
@@ -65,7 +62,6 @@ pub async fn run(quiet: bool) {
             if let Ok(uv_output) = Command::new("uv").arg("--version").output().await {
                 let uv_version_str = String::from_utf8_lossy(&uv_output.stdout);
                 uv_version = uv_version_str
-                    .trim()
                     .split_whitespace()
                     .nth(1)
                     .unwrap_or("unknown")
@@ -74,86 +70,190 @@ pub async fn run(quiet: bool) {
         }
     }
 
-    // Create and write to lox.toml only on first run
-    if is_first_run {
-        // Create TOML content for project configuration
-        let mut toml_content = format!(
-            "[project]\ntype = \"{}\"\nname = \"{}\"\nversion = \"{}\"\n",
-            project.project_type, project.name, project.version
-        );
+    // Get the Python version for pip projects, the one that creates the virtual environment
+    let mut python_version = "unknown".to_string();
+    if project.project_type == "pip" {
+        let python = if cfg!(windows) { "python" } else { "python3" };
+        if let Ok(python_output) = Command::new(python).arg("--version").output().await {
+            let python_version_str = String::from_utf8_lossy(&python_output.stdout);
+            python_version = python_version_str
+                .split_whitespace()
+                .nth(1)
+                .unwrap_or("unknown")
+                .to_string();
+        }
+    }
 
-        // Add Rust-specific build commands if it's a Rust project
-        if project.is_rust_project {
-            toml_content.push_str(
-                format!(
-                    "\n[project.build]\ndev = \"{}\"\nrelease = \"{}\"\n",
-                    project.build_commands.dev, project.build_commands.release
-                )
-                .as_str(),
-            );
-            toml_content.push_str(
-                format!(
-                    "\n[project.run]\ndev = \"{}\"\nrelease = \"{}\"\n",
-                    project.run_commands.dev, project.run_commands.release
-                )
-                .as_str(),
-            );
-            toml_content.push_str("\n[project.commands]\nfmt = \"cargo fmt\"\nlint = \"cargo check\"\ndependency = \"cargo update\"\n");
-            toml_content.push_str(format!("\n[environment]\nos = \"{}\"\narch = \"{}\"\nrustc_version = \"{}\"\ncargo_version = \"{}\"\n",
-                projects::format_os_name(env::consts::OS), env::consts::ARCH, rustc_version, cargo_version).as_str());
-        } else if project.is_uv_project {
-            // Add Python-specific commands for Python projects
-            toml_content.push_str(
-                format!(
-                    "\n[project.build]\ndev = \"{}\"\nrelease = \"{}\"\n",
-                    project.build_commands.dev, project.build_commands.release
-                )
-                .as_str(),
-            );
-            toml_content.push_str(
-                format!(
-                    "\n[project.run]\ndev = \"{}\"\nrelease = \"{}\"\n",
-                    project.run_commands.dev, project.run_commands.release
-                )
-                .as_str(),
-            );
-            toml_content.push_str("\n[project.commands]\nfmt = \"uvx ruff format\"\nlint = \"uvx ruff check\"\ndependency = \"uv update\"\n");
-            toml_content.push_str(
-                format!(
-                    "\n[environment]\nos = \"{}\"\narch = \"{}\"\nuv_version = \"{}\"\n",
-                    projects::format_os_name(env::consts::OS),
-                    env::consts::ARCH,
-                    uv_version
-                )
-                .as_str(),
-            );
-        } else {
-            // Default for other project types
-            toml_content.push_str(
-                format!(
-                    "\n[project.build]\ndev = \"{}\"\nrelease = \"{}\"\n",
-                    project.build_commands.dev, project.build_commands.release
-                )
-                .as_str(),
-            );
-            toml_content.push_str("\n[project.commands]\nfmt = \"unknown\"\nlint = \"unknown\"\ndependency = \"unknown\"\n");
-            toml_content.push_str(
-                format!(
-                    "\n[environment]\nos = \"{}\"\narch = \"{}\"\n",
-                    projects::format_os_name(env::consts::OS),
-                    env::consts::ARCH
-                )
-                .as_str(),
-            );
+    // Get the version of Poetry or PDM for the projects they manage
+    let python_tool = match project.project_type.as_str() {
+        "poetry" => Some(("poetry", "Poetry")),
+        "pdm" => Some(("pdm", "PDM")),
+        _ => None,
+    };
+    let mut tool_version = "unknown".to_string();
+    if let Some((tool, _)) = python_tool
+        && let Ok(tool_output) = Command::new(tool).arg("--version").output().await
+    {
+        // `Poetry (version 2.1.3)` and `PDM, version 2.25.4`
+        let tool_version_str = String::from_utf8_lossy(&tool_output.stdout);
+        tool_version = tool_version_str
+            .split_whitespace()
+            .last()
+            .unwrap_or("unknown")
+            .trim_end_matches(')')
+            .to_string();
+    }
+
+    // Get the CMake version for CMake projects
+    let mut cmake_version = "unknown".to_string();
+    if project.project_type == "cmake"
+        && let Ok(cmake_output) = Command::new("cmake").arg("--version").output().await
+    {
+        // `cmake version 3.28.3`
+        let cmake_version_str = String::from_utf8_lossy(&cmake_output.stdout);
+        cmake_version = cmake_version_str
+            .split_whitespace()
+            .nth(2)
+            .unwrap_or("unknown")
+            .to_string();
+    }
+
+    // Get the Zig version for Zig projects
+    let mut zig_version = "unknown".to_string();
+    if project.project_type == "zig"
+        && let Ok(zig_output) = Command::new("zig").arg("version").output().await
+        && zig_output.status.success()
+    {
+        // `0.13.0`
+        zig_version = String::from_utf8_lossy(&zig_output.stdout)
+            .trim()
+            .to_string();
+    }
+
+    // Get the Go version and the module of Go projects
+    let mut go_version = "unknown".to_string();
+    let go_mod = if project.project_type == "go" {
+        projects::go::read_go_mod().await
+    } else {
+        None
+    };
+    if go_mod.is_some()
+        && let Ok(go_output) = Command::new("go").arg("version").output().await
+    {
+        // `go version go1.22.4 linux/amd64`
+        let go_version_str = String::from_utf8_lossy(&go_output.stdout);
+        go_version = go_version_str
+            .split_whitespace()
+            .nth(2)
+            .map(|version| version.trim_start_matches("go"))
+            .unwrap_or("unknown")
+            .to_string();
+    }
+
+    // Get the Node.js and package manager versions, node prints `v22.1.0` and the managers `10.2.4`
+    let is_node = projects::is_node_type(&project.project_type);
+    let mut node_version = "unknown".to_string();
+    let mut manager_version = "unknown".to_string();
+    if is_node {
+        for (program, version) in [
+            ("node", &mut node_version),
+            (project.project_type.as_str(), &mut manager_version),
+        ] {
+            if let Ok(output) = Command::new(program).arg("--version").output().await
+                && output.status.success()
+            {
+                let version_str = String::from_utf8_lossy(&output.stdout);
+                *version = version_str.trim().trim_start_matches('v').to_string();
+            }
         }
+    }
 
-        // Write the configuration to lox.toml
-        if let Err(e) = write("lox.toml", toml_content) {
-            eprintln!(
-                "Warning: Failed to write project configuration to lox.toml: {}",
-                e
+    // Look inside the conda or pixi environment, for its location and the Python and compilers
+    let is_environment = projects::is_environment_type(&project.project_type);
+    let mut environment_location = "unknown".to_string();
+    let mut environment_tools: Vec<(&str, String)> = Vec::new();
+    if is_environment {
+        if let Some(location) = conda::environment_location(&project.project_type).await {
+            environment_location = location;
+        }
+        // The first compiler found of each kind is reported
+        for (label, programs) in [
+            ("python_version", &["python"][..]),
+            ("fortran_compiler", &["gfortran", "flang"][..]),
+            ("c_compiler", &["gcc", "clang"][..]),
+        ] {
+            let mut version = "unknown".to_string();
+            for program in programs {
+                if let Some(found) =
+                    conda::environment_tool_version(&project.project_type, program).await
+                {
+                    version = found;
+                    break;
+                }
+            }
+            environment_tools.push((label, version));
+        }
+    }
+
+    // Add the doctor sections to lox.toml only on first run
+    if is_first_run {
+        // The project sections are already written, so only fill in commands and environment
+        let saved = projects::update_lox_toml(|document| {
+            let commands = &["project", "commands"];
+            projects::set_toml_string(document, commands, "fmt", &project.commands.fmt);
+            projects::set_toml_string(document, commands, "lint", &project.commands.lint);
+            projects::set_toml_string(
+                document,
+                commands,
+                "dependency",
+                &project.commands.dependency,
             );
-        } else if !quiet {
+
+            let environment = &["environment"];
+            projects::set_toml_string(
+                document,
+                environment,
+                "os",
+                &projects::format_os_name(env::consts::OS),
+            );
+            projects::set_toml_string(document, environment, "arch", env::consts::ARCH);
+            if project.is_rust_project {
+                projects::set_toml_string(document, environment, "rustc_version", &rustc_version);
+                projects::set_toml_string(document, environment, "cargo_version", &cargo_version);
+            } else if project.is_uv_project {
+                projects::set_toml_string(document, environment, "uv_version", &uv_version);
+            } else if let Some((tool, _)) = python_tool {
+                projects::set_toml_string(
+                    document,
+                    environment,
+                    &format!("{}_version", tool),
+                    &tool_version,
+                );
+            } else if project.project_type == "pip" {
+                projects::set_toml_string(document, environment, "python_version", &python_version);
+            } else if project.project_type == "cmake" {
+                projects::set_toml_string(document, environment, "cmake_version", &cmake_version);
+            } else if project.project_type == "go" {
+                projects::set_toml_string(document, environment, "go_version", &go_version);
+            } else if project.project_type == "zig" {
+                projects::set_toml_string(document, environment, "zig_version", &zig_version);
+            } else if is_node {
+                projects::set_toml_string(document, environment, "node_version", &node_version);
+                projects::set_toml_string(
+                    document,
+                    environment,
+                    &format!("{}_version", project.project_type),
+                    &manager_version,
+                );
+            } else if is_environment {
+                projects::set_toml_string(document, environment, "location", &environment_location);
+                for (key, version) in &environment_tools {
+                    projects::set_toml_string(document, environment, key, version);
+                }
+            }
+        });
+
+        if saved && !quiet {
             println!("[TIP] + Project configuration saved to `lox.toml`.");
         }
     }
@@ -175,20 +275,56 @@ pub async fn run(quiet: bool) {
                 "  - Project type:           {} (rust)",
                 project.project_type
             );
-        } else if project.is_uv_project {
+        } else if projects::is_python_type(&project.project_type) {
             println!(
                 "  - Project type:           {} (python)",
                 project.project_type
             );
+        } else if is_environment {
+            println!(
+                "  - Project type:           {} (environment)",
+                project.project_type
+            );
+        } else if project.project_type == "cmake" {
+            println!(
+                "  - Project type:           {} (c/c++)",
+                project.project_type
+            );
+        } else if is_node {
+            println!(
+                "  - Project type:           {} (node)",
+                project.project_type
+            );
         } else {
             println!("  - Project type:           {}", project.project_type);
         }
         println!("  - Project name:           {}", project.name);
         println!("  - Project version:        {}", project.version);
 
+        // Display the module path and the Go version it asks for
+        if let Some(go_mod) = &go_mod {
+            println!(
+                "  - Go module:              {}",
+                go_mod.module.as_deref().unwrap_or("unknown")
+            );
+            println!(
+                "  - Go version (go.mod):    {}",
+                go_mod.go.as_deref().unwrap_or("unknown")
+            );
+        }
+
         // Display project virtual env for Python projects
         if project.is_uv_project {
             println!("  - Project virtual env:    unknown");
+        } else if project.project_type == "pip" {
+            let venv = if metadata(projects::python::VENV_DIR).await.is_ok() {
+                projects::python::VENV_DIR
+            } else {
+                "unknown"
+            };
+            println!("  - Project virtual env:    {}", venv);
+        } else if is_environment {
+            println!("  - Project environment:    {}", environment_location);
         }
 
         // Display project commands based on project type
@@ -198,21 +334,61 @@ pub async fn run(quiet: bool) {
                 "  - Project build(release): {}",
                 project.build_commands.release
             );
-            println!("  - Project fmt:            cargo fmt");
-            println!("  - Project lint:           cargo check");
-            println!("  - Project dependency:     cargo update");
-        } else if project.is_uv_project {
+        } else if projects::is_python_type(&project.project_type) {
+            println!("  - Project build:          {}", project.build_commands.dev);
+        } else if projects::is_runner_type(&project.project_type) {
+            // Both profiles run the same target, and every target is a task
             println!("  - Project build:          {}", project.build_commands.dev);
-            println!("  - Project fmt:            uvx ruff format");
-            println!("  - Project lint:           uvx ruff check");
-            println!("  - Project dependency:     uv update");
+            let targets: Vec<&str> = project
+                .user_tasks
+                .iter()
+                .filter(|task| task.cmd == project.project_type)
+                .map(|task| task.name.as_str())
+                .collect();
+            println!("  - Project targets:        {}", targets.join(", "));
+        } else if is_environment
+            || is_node
+            || projects::is_native_type(&project.project_type)
+            || projects::is_custom_type(&project.project_type)
+        {
+            println!("  - Project build(dev):     {}", project.build_commands.dev);
+            println!(
+                "  - Project build(release): {}",
+                project.build_commands.release
+            );
         } else {
             println!("  - Project build(dev):     unknown");
             println!("  - Project build(release): unknown");
-            println!("  - Project fmt:            unknown");
-            println!("  - Project lint:           unknown");
-            println!("  - Project dependency:     unknown");
         }
+        // List the packages of a Cargo workspace
+        let members = if project.is_rust_project {
+            projects::cargo::detect_workspace_members().await
+        } else {
+            Vec::new()
+        };
+        if !members.is_empty() {
+            println!("  - Workspace members:      {}", members.len());
+            let width = members
+                .iter()
+                .map(|member| member.name.len())
+                .max()
+                .unwrap_or(0);
+            for member in &members {
+                println!(
+                    "    - {:<width$} | {} | {}",
+                    member.name,
+                    member.kind(),
+                    member.version,
+                    width = width
+                );
+            }
+        }
+        println!("  - Project fmt:            {}", project.commands.fmt);
+        println!("  - Project lint:           {}", project.commands.lint);
+        println!(
+            "  - Project dependency:     {}",
+            project.commands.dependency
+        );
         println!();
         println!("[2/2] + Environment informations");
 
@@ -232,6 +408,42 @@ pub async fn run(quiet: bool) {
         if project.is_uv_project {
             println!("  - uv version:            {}", uv_version);
         }
+
+        if project.project_type == "pip" {
+            println!("  - Python version:        {}", python_version);
+        }
+
+        if project.project_type == "cmake" {
+            println!("  - CMake version:         {}", cmake_version);
+        }
+
+        if project.project_type == "go" {
+            println!("  - Go version:            {}", go_version);
+        }
+
+        if project.project_type == "zig" {
+            println!("  - Zig version:           {}", zig_version);
+        }
+
+        if is_node {
+            println!("  - Node.js version:       {}", node_version);
+            let label = format!("{} version:", project.project_type);
+            println!("  - {:<23}{}", label, manager_version);
+        }
+
+        if let Some((_, tool_name)) = python_tool {
+            let label = format!("{} version:", tool_name);
+            println!("  - {:<23}{}", label, tool_version);
+        }
+
+        for (key, version) in &environment_tools {
+            let label = match *key {
+                "python_version" => "Python version:",
+                "fortran_compiler" => "Fortran compiler:",
+                _ => "C compiler:",
+            };
+            println!("  - {:<23}{}", label, version);
+        }
         println!();
 
         println!("[TIP] + Everything is Up-to-date.");
@@ -244,4 +456,6 @@ pub async fn run(quiet: bool) {
         println!("[TIP] + [Task End]");
         println!();
     }
+
+    true
 }
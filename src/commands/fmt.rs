@@ -0,0 +1,59 @@
+use crate::projects;
+use crate::tasks::{self, StepFailure};
+use std::time::Instant;
+
+pub async fn run(check: bool) -> bool {
+    println!();
+
+    // Get project information
+    let project = projects::get_or_create_project().await;
+
+    // The check mode only reports unformatted files, so it is safe to run in CI
+    let command = if check {
+        project.commands.fmt_check.clone()
+    } else {
+        Some(project.commands.fmt.clone())
+    };
+    let (key, step) = if check {
+        ("fmt_check", "Check the formatting")
+    } else {
+        ("fmt", "Format the project")
+    };
+
+    println!("[TIP] + {}.", step);
+    run_project_command(key, command.as_deref(), step).await
+}
+
+// Run a command from `[project.commands]` as the only step, with the usual reporting
+pub async fn run_project_command(key: &str, command: Option<&str>, step: &str) -> bool {
+    let Some(command) = command.filter(|command| *command != "unknown") else {
+        println!(
+            "[TIP] + No `{}` command found, set it in the `[project.commands]` table of `lox.toml`.",
+            key
+        );
+        println!("[TIP] + [Task End]");
+        println!();
+        return false;
+    };
+    println!();
+
+    // Start timer for all tasks
+    let start_time = Instant::now();
+
+    println!("[1/1] + {}", step);
+    let result = tasks::execute_command(key, command).await;
+
+    println!();
+    if let Err(failed_tasks) = &result {
+        StepFailure::new(1, 1, step, failed_tasks.clone()).report();
+    }
+
+    // Calculate and display total elapsed time
+    let elapsed = start_time.elapsed();
+    let elapsed_seconds = elapsed.as_secs_f64();
+    println!("[TIP] + Done the tasks in {:.2}s.", elapsed_seconds);
+
+    println!("[TIP] + [Task End]");
+    println!();
+    result.is_ok()
+}
@@ -0,0 +1,23 @@
+use crate::commands::fmt::run_project_command;
+use crate::projects;
+
+pub async fn run(fix: bool) -> bool {
+    println!();
+
+    // Get project information
+    let project = projects::get_or_create_project().await;
+
+    let command = if fix {
+        project.commands.lint_fix.clone()
+    } else {
+        Some(project.commands.lint.clone())
+    };
+    let (key, step) = if fix {
+        ("lint_fix", "Lint and fix the project")
+    } else {
+        ("lint", "Lint the project")
+    };
+
+    println!("[TIP] + {}.", step);
+    run_project_command(key, command.as_deref(), step).await
+}
@@ -1,5 +1,11 @@
 pub mod build;
+pub mod clean;
 pub mod dash;
 pub mod dev;
 pub mod doctor;
+pub mod fmt;
+pub mod lint;
 pub mod run;
+pub mod task;
+pub mod test;
+pub mod watch;
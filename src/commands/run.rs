@@ -1,135 +1,587 @@
-use crate::projects;
-use crate::tasks;
+use crate::commands::build::{missing_setting, missing_target, select_package};
+use crate::projects::{
+    self,
+    cargo::{MetadataPackage, cargo_metadata},
+    cmake, go, is_custom_type, is_environment_type, is_native_type, is_node_type, is_python_type,
+    is_runner_type, make, node, python, zig,
+};
+use crate::tasks::{self, PipelineKind, StepFailure};
+use std::env::current_exe;
 use std::fs::metadata;
 use std::time::Instant;
 use tokio::process::Command;
 
-pub async fn run() {
+pub async fn run(
+    package: Option<String>,
+    binary: Option<String>,
+    example: Option<String>,
+    script: Option<String>,
+    args: Vec<String>,
+) -> bool {
     println!();
 
     // Get project information
     let project = projects::get_or_create_project().await;
+    if !select_package(&project, package.as_deref()).await {
+        return false;
+    }
 
-    if project.is_rust_project || project.is_uv_project || project.is_fortran_project {
-        // Check if it's a library project
-        if project.is_library {
-            println!(
-                "[TIP] + The current project is a library(lib) project, which doesn't have binary output."
-            );
+    let is_python_project = is_python_type(&project.project_type);
+    let is_node_project = is_node_type(&project.project_type);
+    let is_environment = is_environment_type(&project.project_type);
+    let is_runner = is_runner_type(&project.project_type);
+    let is_custom = is_custom_type(&project.project_type);
+    if project.is_rust_project
+        || is_python_project
+        || is_node_project
+        || project.is_fortran_project
+        || is_environment
+        || is_runner
+        || is_custom
+        || is_native_type(&project.project_type)
+    {
+        let target_release = if is_python_project {
+            resolve_script(&project, script.as_deref(), &project.run_commands.release).await
+        } else if is_node_project {
+            resolve_node_script(&project, script.as_deref(), &project.run_commands.release).await
+        } else if script.is_some() {
+            println!("[TIP] + `lox run <script>` only applies to Python and Node.js projects.");
             println!("[TIP] + [Task End]");
             println!();
-            return;
-        }
-        run_project(&project).await;
+            None
+        } else if is_environment {
+            resolve_environment_command(&project.run_commands.release)
+        } else if is_runner {
+            resolve_target_command(&project, &project.run_commands.release)
+        } else if is_custom {
+            resolve_custom_command(&project.run_commands.release, "release")
+        } else {
+            resolve_binary(
+                &project,
+                package.as_deref(),
+                binary.as_deref(),
+                example.as_deref(),
+                "release",
+                &project.run_commands.release,
+            )
+            .await
+        };
+        let Some(target_release) = target_release else {
+            return false;
+        };
+        run_project(
+            &project,
+            &target_release,
+            package.as_deref(),
+            example.as_deref(),
+            &args,
+        )
+        .await
     } else {
         println!("[TIP] + Unknown project type. No run configuration found.");
         println!("[TIP] + [Task End]");
         println!();
+        false
     }
 }
 
-async fn run_project(project: &projects::Project) {
-    let target_release = &project.run_commands.release;
-
+async fn run_project(
+    project: &projects::Project,
+    target_release: &str,
+    package: Option<&str>,
+    example: Option<&str>,
+    args: &[String],
+) -> bool {
     // Start timer for all tasks
     let overall_start_time = Instant::now();
 
-    // Check if binary exists for Rust or Fortran projects
-    if project.is_rust_project || project.is_fortran_project {
-        // Determine the target directory based on project type
-        let is_fpm = project.project_type == "fpm";
-        let target_dir = if is_fpm { "./build" } else { "./target" };
-        let target_msg = if is_fpm { "build" } else { "target" };
-
-        match metadata(target_dir) {
-            Ok(_) => {
-                // For FPM, we don't check the exact binary path since it's managed by FPM
-                if !is_fpm && !metadata(target_release).is_ok() {
-                    println!("[TIP] + Nothing at `{}` .", target_msg);
-                    println!();
-                    println!("[1/2] + Build the project first.");
-
-                    // Run lox build
-                    println!("  - Task | lox build | ");
-                    let build_status = Command::new("lox")
-                        .arg("build")
-                        .status()
-                        .await
-                        .expect("Failed to execute lox build");
-                    println!(
-                        "  - Task | lox build | {}.",
-                        if build_status.success() {
-                            "Done"
-                        } else {
-                            "Failed"
-                        }
-                    );
-
-                    println!();
-                }
-            }
-            Err(_) => {
-                println!("[TIP] + Nothing at `{}` .", target_msg);
-                println!();
-                println!("[1/2] + Build the project first.");
-
-                // Run lox build
-                println!("  - Task | lox build | ");
-                let build_status = Command::new("lox")
-                    .arg("build")
-                    .status()
-                    .await
-                    .expect("Failed to execute lox build");
-                println!(
-                    "  - Task | lox build | {}.",
-                    if build_status.success() {
-                        "Done"
-                    } else {
-                        "Failed"
-                    }
-                );
+    // Stages to run before the program, from `[pipeline.run]` or the project type
+    let prepare_stages = tasks::resolve_pipeline(project, PipelineKind::Run)
+        .await
+        .map(|pipeline| pipeline.stages)
+        .unwrap_or_default();
+    let total = prepare_stages.len().max(1) + 1;
+
+    let prepared = if !prepare_stages.is_empty() {
+        tasks::run_stages(&prepare_stages, total).await
+    } else if (project.is_rust_project
+        || project.is_fortran_project
+        || is_native_type(&project.project_type))
+        && needs_build(project, target_release)
+    {
+        match example {
+            Some(example) => build_example(example, package, "release", total).await,
+            None => build_first(project, "build", package, total).await,
+        }
+    } else {
+        Ok(())
+    };
+
+    let result = match prepared {
+        Ok(()) => {
+            println!("[{}/{}] + Run the project.", total, total);
+            run_command(target_release, args, total).await
+        }
+        Err(failure) => Err(failure),
+    };
+
+    if let Err(failure) = &result {
+        failure.report();
+    }
+
+    // Calculate and display total elapsed time for all tasks
+    let overall_elapsed = overall_start_time.elapsed();
+    let overall_elapsed_seconds = overall_elapsed.as_secs_f64();
+    println!("[TIP] + Done the tasks in {:.2}s.", overall_elapsed_seconds);
+
+    println!("[TIP] + [Task End]");
+    println!();
+    result.is_ok()
+}
+
+// Get the program to run, the binary or example picked with `-p`, `--bin` and `--example` for a
+// cargo project, or the executable picked with `--bin` for a CMake, Go or Zig project
+//
+// Prints why and returns `None` when there is nothing to run, like the other resolvers below.
+pub async fn resolve_binary(
+    project: &projects::Project,
+    package: Option<&str>,
+    binary: Option<&str>,
+    example: Option<&str>,
+    profile: &str,
+    configured: &str,
+) -> Option<String> {
+    if is_native_type(&project.project_type) {
+        let release = profile == "release";
+        return resolve_executable(project, binary, release, configured).await;
+    }
+
+    if !project.is_rust_project {
+        return Some(configured.to_string());
+    }
+
+    // The configured command runs unless a binary has to be picked, saved binary paths are
+    // resolved again so they follow the target directory
+    let picking =
+        package.is_some() || binary.is_some() || example.is_some() || configured == "unknown";
+    let cargo = cargo_metadata().await;
+    if !picking {
+        let saved = cargo.as_ref().and_then(|cargo| {
+            let binary = cargo.saved_binary(configured)?;
+            Some(cargo.binary_path(profile, binary))
+        });
+        return Some(saved.unwrap_or_else(|| configured.to_string()));
+    }
+
+    let Some(cargo) = cargo else {
+        eprintln!("[ERROR] + Failed to read the targets of the project with `cargo metadata`.");
+        println!("[TIP] + [Task End]");
+        println!();
+        return None;
+    };
+
+    // Look in the picked package, or in the root package unless a target is named
+    let named = binary.is_some() || example.is_some();
+    let packages: Vec<&MetadataPackage> = match (package, cargo.root_package()) {
+        (Some(package), _) => cargo
+            .packages
+            .iter()
+            .filter(|candidate| candidate.name == package)
+            .collect(),
+        (None, Some(root)) if !named => vec![root],
+        (None, _) => cargo.packages.iter().collect(),
+    };
+    let examples: Vec<&str> = packages
+        .iter()
+        .flat_map(|package| package.examples())
+        .collect();
+
+    if let Some(example) = example {
+        if examples.contains(&example) {
+            return Some(cargo.example_path(profile, example));
+        }
+        if examples.is_empty() {
+            eprintln!("[ERROR] + No example named `{}`, found none.", example);
+        } else {
+            eprintln!(
+                "[ERROR] + No example named `{}`, found: {}.",
+                example,
+                examples.join(", ")
+            );
+        }
+        println!("[TIP] + [Task End]");
+        println!();
+        return None;
+    }
+
+    let binaries: Vec<&str> = packages
+        .iter()
+        .flat_map(|package| package.binaries())
+        .collect();
+
+    let picked = match binary {
+        Some(binary) => binaries.iter().copied().find(|name| *name == binary),
+        None if packages.len() == 1 => packages[0].default_binary(),
+        None if binaries.len() == 1 => Some(binaries[0]),
+        None => None,
+    };
+    if let Some(picked) = picked {
+        return Some(cargo.binary_path(profile, picked));
+    }
+
+    let name = package.unwrap_or(&project.name);
+    if binaries.is_empty() && examples.is_empty() {
+        println!(
+            "[TIP] + The `{}` package is a library(lib), which doesn't have binary output.",
+            name
+        );
+    } else if binaries.is_empty() {
+        println!(
+            "[TIP] + The `{}` package is a library(lib), run one of its examples with `--example`: {}.",
+            name,
+            examples.join(", ")
+        );
+    } else if let Some(binary) = binary {
+        eprintln!(
+            "[ERROR] + No binary named `{}`, found: {}.",
+            binary,
+            binaries.join(", ")
+        );
+    } else {
+        println!(
+            "[TIP] + Found {} binaries, pick one with `--bin`: {}.",
+            binaries.len(),
+            binaries.join(", ")
+        );
+    }
+    println!("[TIP] + [Task End]");
+    println!();
+    None
+}
 
+// Get the executable of a CMake, Go or Zig project, the one picked with `--bin`
+//
+// CMake projects list their executables through the File API, Go modules their main packages,
+// and Zig projects the executables installed to `zig-out/bin`, which are built first if missing.
+async fn resolve_executable(
+    project: &projects::Project,
+    binary: Option<&str>,
+    release: bool,
+    configured: &str,
+) -> Option<String> {
+    if binary.is_none() && configured != "unknown" {
+        return Some(configured.to_string());
+    }
+
+    let executables = if project.project_type == "cmake" {
+        // The File API answers once the build directory is configured
+        let dir = cmake::build_dir(release);
+        let mut executables = cmake::executables(dir).await;
+        if executables.is_none() && cmake::configure(release).await {
+            executables = cmake::executables(dir).await;
+        }
+        executables
+    } else if project.project_type == "zig" {
+        if zig::executables().await.is_none() {
+            let subcommand = if release { "build" } else { "dev" };
+            if let Err(failure) = build_first(project, subcommand, None, 2).await {
+                failure.report();
+                println!("[TIP] + [Task End]");
                 println!();
+                return None;
             }
         }
+        Some(zig::executables().await.unwrap_or_default())
+    } else {
+        Some(go::binaries(release).await)
+    };
+    let Some(executables) = executables else {
+        eprintln!("[ERROR] + Failed to read the targets of the project with the CMake File API.");
+        println!("[TIP] + [Task End]");
+        println!();
+        return None;
+    };
+
+    let names: Vec<&str> = executables
+        .iter()
+        .map(|executable| executable.name.as_str())
+        .collect();
+    let picked = match binary {
+        Some(binary) => executables
+            .iter()
+            .find(|executable| executable.name == binary),
+        None if executables.len() == 1 => executables.first(),
+        None => None,
+    };
+    if let Some(picked) = picked {
+        return Some(picked.path.clone());
+    }
+
+    if executables.is_empty() {
+        println!("[TIP] + The project only has libraries, which don't have binary output.");
+    } else if let Some(binary) = binary {
+        eprintln!(
+            "[ERROR] + No executable named `{}`, found: {}.",
+            binary,
+            names.join(", ")
+        );
+    } else {
+        println!(
+            "[TIP] + Found {} executables, pick one with `--bin`: {}.",
+            names.len(),
+            names.join(", ")
+        );
+    }
+    println!("[TIP] + [Task End]");
+    println!();
+    None
+}
+
+// Get the command to run a Python project, the script picked with `lox run <script>`
+pub async fn resolve_script(
+    project: &projects::Project,
+    script: Option<&str>,
+    configured: &str,
+) -> Option<String> {
+    if script.is_none() && configured != "unknown" {
+        return Some(configured.to_string());
+    }
 
-        println!("[2/2] + Run the project.");
-    } else if project.is_uv_project {
-        // For UV projects, lock dependencies first
-        println!("[1/2] + Lock the project dependencies.");
-        tasks::execute_task_by_id(tasks::UV_LOCK).await;
+    let pyproject = python::read_pyproject().await;
+    let entry_points = python::detect_entry_points(pyproject.as_ref()).await;
+    let names = entry_points.names();
+    let target = match script {
+        Some(script) => entry_points
+            .find(script)
+            .map(|entry_point| entry_point.target.clone()),
+        None => entry_points.default_target(),
+    };
+    if let Some(target) = target {
+        return Some(python::run_command(&project.project_type, &target));
+    }
+
+    if let Some(script) = script {
+        eprintln!(
+            "[ERROR] + No script named `{}`, found: {}.",
+            script,
+            if names.is_empty() {
+                String::from("none")
+            } else {
+                names.join(", ")
+            }
+        );
+    } else if names.is_empty() {
+        println!(
+            "[TIP] + Nothing to run, add `[project.scripts]` or `[tool.lox] entry` to pyproject.toml."
+        );
+    } else {
+        println!(
+            "[TIP] + Found {} scripts, pick one with `lox run <script>`: {}.",
+            names.len(),
+            names.join(", ")
+        );
+    }
+    println!("[TIP] + [Task End]");
+    println!();
+    None
+}
+
+// Get the command to run a Node.js package, the script of package.json picked with
+// `lox run <script>`
+pub async fn resolve_node_script(
+    project: &projects::Project,
+    script: Option<&str>,
+    configured: &str,
+) -> Option<String> {
+    let Some(script) = script else {
+        if configured != "unknown" {
+            return Some(configured.to_string());
+        }
+        println!("[TIP] + Nothing to run, add a `start` script or a `main` file to package.json.");
+        println!("[TIP] + [Task End]");
         println!();
+        return None;
+    };
 
-        println!("[2/2] + Run the project.");
+    let package = node::read_package_json().await;
+    if package.has_script(script) {
+        return Some(node::script_command(&project.project_type, script));
     }
+    let names = package.script_names();
+    eprintln!(
+        "[ERROR] + No script named `{}`, found: {}.",
+        script,
+        if names.is_empty() {
+            String::from("none")
+        } else {
+            names.join(", ")
+        }
+    );
+    println!("[TIP] + [Task End]");
+    println!();
+    None
+}
 
-    // Run the release command and measure its time
-    println!("  - Task | {} | ", target_release);
-    let command_start_time = Instant::now();
+// Get the command to run a project inside its conda or pixi environment
+pub fn resolve_environment_command(configured: &str) -> Option<String> {
+    if configured != "unknown" {
+        return Some(configured.to_string());
+    }
+    println!("[TIP] + Nothing to run in the environment, set `[project.run]` in lox.toml.");
+    println!("[TIP] + [Task End]");
+    println!();
+    None
+}
+
+// Get the command running the `run` target of a Makefile or justfile project
+pub fn resolve_target_command(project: &projects::Project, configured: &str) -> Option<String> {
+    if configured != "unknown" {
+        return Some(configured.to_string());
+    }
+    missing_target(&project.project_type, &[make::RUN_TARGET]);
+    None
+}
+
+// Get the `[project.run]` command of a custom project for a profile
+pub fn resolve_custom_command(configured: &str, profile: &str) -> Option<String> {
+    if configured != "unknown" {
+        return Some(configured.to_string());
+    }
+    missing_setting(&format!("`[project.run].{}`", profile));
+    None
+}
+
+// Check whether the binary of a Rust or Fortran project has to be built first
+pub fn needs_build(project: &projects::Project, binary: &str) -> bool {
+    // For FPM, we don't check the exact binary path since it's managed by FPM
+    if project.project_type == "fpm" {
+        metadata("./build").is_err()
+    } else {
+        metadata(binary).is_err()
+    }
+}
+
+// Build the project with another lox command, as the first of `total` steps
+pub async fn build_first(
+    project: &projects::Project,
+    subcommand: &str,
+    package: Option<&str>,
+    total: usize,
+) -> Result<(), StepFailure> {
+    let mut lox_args = vec![subcommand];
+    if let Some(package) = package {
+        lox_args.extend(["-p", package]);
+    }
+    let task_name = format!("lox {}", lox_args.join(" "));
+    let target_msg = if project.project_type == "fpm" {
+        "build"
+    } else if project.project_type == "zig" {
+        zig::OUT_DIR
+    } else {
+        "target"
+    };
 
-    // Split command into binary and arguments for proper execution
-    let mut parts = target_release.split_whitespace();
-    let run_status = if let Some(binary) = parts.next() {
-        let args: Vec<&str> = parts.collect();
-        Command::new(binary)
-            .args(args)
+    println!("[TIP] + Nothing at `{}` .", target_msg);
+    println!();
+    println!("[1/{}] + Build the project first.", total);
+
+    // Run the same lox binary, lox may not be on the PATH
+    println!("  - Task | {} | ", task_name);
+    let built = match current_exe() {
+        Ok(lox) => Command::new(lox)
+            .args(&lox_args)
             .status()
             .await
-            .expect(format!("Failed to execute {}", target_release).as_str())
+            .is_ok_and(|status| status.success()),
+        Err(_) => false,
+    };
+    println!(
+        "  - Task | {} | {}.",
+        task_name,
+        if built { "Done" } else { "Failed" }
+    );
+    println!();
+
+    if built {
+        Ok(())
     } else {
-        panic!("Empty command string")
+        Err(StepFailure::new(
+            1,
+            total,
+            "Build the project first.",
+            vec![task_name],
+        ))
+    }
+}
+
+// Build a cargo example with a profile, as the first of `total` steps
+pub async fn build_example(
+    example: &str,
+    package: Option<&str>,
+    profile: &str,
+    total: usize,
+) -> Result<(), StepFailure> {
+    let mut command = String::from("cargo build");
+    if profile == "release" {
+        command.push_str(" --release");
+    }
+    command.push_str(&format!(" --example {}", example));
+    if let Some(package) = package {
+        command.push_str(&format!(" -p {}", package));
+    }
+
+    println!("[TIP] + Nothing at `target` .");
+    println!();
+    println!("[1/{}] + Build the example first.", total);
+    let result = tasks::execute_command(&command, &command).await;
+    println!();
+
+    result.map_err(|failed_tasks| {
+        StepFailure::new(1, total, "Build the example first.", failed_tasks)
+    })
+}
+
+// Run the project command as the last of `total` steps, and measure its time
+//
+// `extra_args` are passed to the program as-is, after the configured arguments.
+pub async fn run_command(
+    command: &str,
+    extra_args: &[String],
+    total: usize,
+) -> Result<(), StepFailure> {
+    // Split the command like a shell would, so quoted arguments stay whole, and only then add
+    // the arguments passed through, so they never stand in for a malformed command
+    let mut words = match shlex::split(command) {
+        Some(words) if !words.is_empty() => words,
+        _ => {
+            return Err(StepFailure::new(
+                total,
+                total,
+                "Run the project.",
+                vec![format!("invalid command in lox.toml `{}`", command)],
+            ));
+        }
     };
+    words.extend(extra_args.iter().cloned());
+    let display_command =
+        shlex::try_join(words.iter().map(String::as_str)).unwrap_or_else(|_| words.join(" "));
+
+    println!("  - Task | {} | ", display_command);
+    let command_start_time = Instant::now();
+
+    let (binary, args) = words.split_at(1);
+    let run_status = Command::new(&binary[0]).args(args).status().await;
+    let success = run_status.as_ref().is_ok_and(|status| status.success());
+    if let Err(e) = &run_status {
+        eprintln!("[ERROR] + Failed to execute {}: {}", display_command, e);
+    }
 
     let command_elapsed = command_start_time.elapsed();
     let command_elapsed_seconds = command_elapsed.as_secs_f64();
     println!(
         "  - Task | {} | {}.",
-        target_release,
-        if run_status.success() {
-            "Done"
-        } else {
-            "Failed"
-        }
+        display_command,
+        if success { "Done" } else { "Failed" }
     );
 
     println!();
@@ -138,11 +590,14 @@ async fn run_project(project: &projects::Project) {
         command_elapsed_seconds
     );
 
-    // Calculate and display total elapsed time for all tasks
-    let overall_elapsed = overall_start_time.elapsed();
-    let overall_elapsed_seconds = overall_elapsed.as_secs_f64();
-    println!("[TIP] + Done the tasks in {:.2}s.", overall_elapsed_seconds);
-
-    println!("[TIP] + [Task End]");
-    println!();
+    if success {
+        Ok(())
+    } else {
+        Err(StepFailure::new(
+            total,
+            total,
+            "Run the project.",
+            vec![display_command],
+        ))
+    }
 }
@@ -0,0 +1,61 @@
+use crate::projects;
+use crate::tasks::{self, StepFailure};
+use std::time::Instant;
+
+pub async fn run(name: Option<String>, list: bool) -> bool {
+    println!();
+
+    // Load the project so the tasks from lox.toml are registered
+    let _project = projects::get_or_create_project().await;
+
+    match name {
+        Some(name) if !list => run_task(&name).await,
+        _ => {
+            list_tasks();
+            true
+        }
+    }
+}
+
+fn list_tasks() {
+    println!("[TIP] + Available tasks.");
+    println!();
+
+    let tasks = tasks::list_tasks();
+    let width = tasks.iter().map(|(id, _)| id.len()).max().unwrap_or(0);
+
+    println!("[1/1] + Task list");
+    for (id, description) in tasks {
+        println!("  - {:<width$} | {}", id, description, width = width);
+    }
+
+    println!();
+    println!("[TIP] + Run one with `lox task <name>`.");
+    println!("[TIP] + [Task End]");
+    println!();
+}
+
+async fn run_task(name: &str) -> bool {
+    println!("[TIP] + Run the `{}` task.", name);
+    println!();
+
+    // Start timer for all tasks
+    let start_time = Instant::now();
+
+    println!("[1/1] + Run the task");
+    let result = tasks::execute_tasks(&[name.to_string()]).await;
+
+    println!();
+    if let Err(failed_tasks) = &result {
+        StepFailure::new(1, 1, "Run the task", failed_tasks.clone()).report();
+    }
+
+    // Calculate and display total elapsed time
+    let elapsed = start_time.elapsed();
+    let elapsed_seconds = elapsed.as_secs_f64();
+    println!("[TIP] + Done the tasks in {:.2}s.", elapsed_seconds);
+
+    println!("[TIP] + [Task End]");
+    println!();
+    result.is_ok()
+}
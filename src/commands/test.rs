@@ -0,0 +1,57 @@
+use crate::commands::build::{missing_setting, missing_target, select_package};
+use crate::projects::{self, custom, is_custom_type, is_runner_type, make};
+use crate::tasks::{self, PipelineKind};
+use std::time::Instant;
+
+pub async fn run(package: Option<String>) -> bool {
+    println!();
+
+    // Get project information
+    let project = projects::get_or_create_project().await;
+    if !select_package(&project, package.as_deref()).await {
+        return false;
+    }
+
+    if let Some(pipeline) = tasks::resolve_pipeline(&project, PipelineKind::Test).await {
+        test_project(&pipeline).await
+    } else if is_runner_type(&project.project_type) {
+        missing_target(&project.project_type, &[make::TEST_TARGET])
+    } else if is_custom_type(&project.project_type) {
+        missing_setting(&format!("`[tasks.{}]`", custom::TEST_TASK))
+    } else {
+        println!("[TIP] + Unknown project type. No test configuration found.");
+        println!("[TIP] + [Task End]");
+        println!();
+        false
+    }
+}
+
+async fn test_project(pipeline: &projects::Pipeline) -> bool {
+    println!("[TIP] + Test the project.");
+    println!();
+
+    // Start timer for all tasks
+    let start_time = Instant::now();
+
+    // Execute the stages using the task system, stopping at the first failure
+    let result = tasks::run_stages(&pipeline.stages, pipeline.stages.len()).await;
+
+    // Test tasks count the tests they run, when the test runner reports them
+    let (passed, failed) = tasks::test_counts();
+    if passed + failed > 0 {
+        println!("[TIP] + Passed [{}/{}] tests.", passed, passed + failed);
+    }
+
+    if let Err(failure) = &result {
+        failure.report();
+    }
+
+    // Calculate and display total elapsed time
+    let elapsed = start_time.elapsed();
+    let elapsed_seconds = elapsed.as_secs_f64();
+    println!("[TIP] + Done the tasks in {:.2}s.", elapsed_seconds);
+
+    println!("[TIP] + [Task End]");
+    println!();
+    result.is_ok()
+}
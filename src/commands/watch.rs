@@ -0,0 +1,421 @@
+use crate::commands::build::missing_setting;
+use crate::projects;
+use globset::{Glob, GlobSet, GlobSetBuilder};
+use notify::{Event, EventKind, RecursiveMode, Watcher};
+use std::env::{current_dir, current_exe};
+use std::path::{Path, PathBuf};
+use std::process::ExitStatus;
+use std::time::Duration;
+use tokio::process::{Child, Command};
+use tokio::sync::mpsc::{UnboundedReceiver, unbounded_channel};
+use tokio::time::timeout;
+
+// Time without changes before the command reruns
+const DEBOUNCE: Duration = Duration::from_millis(300);
+
+// Time a cancelled command gets to stop before it is killed
+const CANCEL_GRACE: Duration = Duration::from_secs(2);
+
+// Outputs and environments never trigger a rerun, so builds don't restart themselves
+const DEFAULT_IGNORE: [&str; 10] = [
+    "target/**",
+    "build/**",
+    "dist/**",
+    ".venv/**",
+    ".git/**",
+    "**/__pycache__/**",
+    ".pixi/**",
+    "**/node_modules/**",
+    "zig-out/**",
+    ".zig-cache/**",
+];
+
+// The commands `lox watch` can rerun
+#[derive(Debug, Clone, Copy, clap::ValueEnum)]
+pub enum WatchCommand {
+    Dev,
+    Dash,
+    Test,
+    Build,
+}
+
+impl WatchCommand {
+    // Get the lox subcommand to run
+    fn subcommand(self) -> &'static str {
+        match self {
+            WatchCommand::Dev => "dev",
+            WatchCommand::Dash => "dash",
+            WatchCommand::Test => "test",
+            WatchCommand::Build => "build",
+        }
+    }
+}
+
+// A lox command started by the watch
+struct Running {
+    child: Child,
+    // Whether its process group was made the foreground group of the terminal
+    owns_terminal: bool,
+}
+
+// The directories to watch, and the source files in them that trigger a rerun
+struct WatchSpec {
+    roots: Vec<(&'static str, RecursiveMode)>,
+    sources: Vec<String>,
+}
+
+pub async fn run(command: WatchCommand, package: Option<String>, jobs: Option<usize>) -> bool {
+    println!();
+
+    // Get project information
+    let project = projects::get_or_create_project().await;
+
+    // lox knows nothing of the sources of a custom project, or of where its builds write
+    if projects::is_custom_type(&project.project_type) && project.watch.sources.is_empty() {
+        return missing_setting("`[watch].sources`");
+    }
+    let Some(spec) = watch_spec(&project).await else {
+        println!("[TIP] + Unknown project type. No watch configuration found.");
+        println!("[TIP] + [Task End]");
+        println!();
+        return false;
+    };
+
+    let sources = build_glob_set(spec.sources.iter().map(String::as_str));
+    let ignore = build_glob_set(
+        DEFAULT_IGNORE
+            .iter()
+            .copied()
+            .chain(project.watch.ignore.iter().map(String::as_str)),
+    );
+    let root = current_dir()
+        .and_then(|dir| dir.canonicalize())
+        .unwrap_or_else(|_| PathBuf::from("."));
+
+    // The watcher runs on its own thread and forwards the events to this task
+    let (sender, mut events) = unbounded_channel();
+    let mut watcher = match notify::recommended_watcher(move |event: notify::Result<Event>| {
+        if let Ok(event) = event {
+            let _ = sender.send(event);
+        }
+    }) {
+        Ok(watcher) => watcher,
+        Err(e) => {
+            eprintln!("[ERROR] + Failed to watch the project: {}", e);
+            return false;
+        }
+    };
+    for (path, mode) in &spec.roots {
+        if let Err(e) = watcher.watch(&root.join(path), *mode) {
+            eprintln!("Warning: Failed to watch `{}`: {}", path, e);
+        }
+    }
+
+    let subcommand = command.subcommand();
+    println!(
+        "[TIP] + Watch {}, rerun `lox {}` on changes. Press Ctrl-C to stop.",
+        spec.sources
+            .iter()
+            .map(|source| format!("`{}`", source))
+            .collect::<Vec<_>>()
+            .join(", "),
+        subcommand
+    );
+
+    // `lox dash` runs the program of the project, which may read the terminal
+    let interactive = matches!(command, WatchCommand::Dash);
+    let mut running = start(subcommand, package.as_deref(), jobs, interactive);
+    loop {
+        tokio::select! {
+            event = events.recv() => {
+                let Some(event) = event else {
+                    break;
+                };
+                let Some(changed) = changed_source(&event, &root, &sources, &ignore) else {
+                    continue;
+                };
+
+                // Wait for the burst of changes of a save or a checkout to settle
+                debounce(&mut events).await;
+
+                if let Some(child) = running.take() {
+                    println!();
+                    println!("[TIP] + Cancel the running `lox {}`.", subcommand);
+                    cancel(child).await;
+                }
+                println!();
+                println!("[TIP] + Changed `{}`, rerun `lox {}`.", changed, subcommand);
+                running = start(subcommand, package.as_deref(), jobs, interactive);
+            }
+            status = wait(&mut running) => {
+                if let Some(done) = running.take() {
+                    take_terminal(&done);
+                }
+                let result = if status.is_some_and(|status| status.success()) {
+                    "Done"
+                } else {
+                    "Failed"
+                };
+                println!("[TIP] + `lox {}` | {}. Waiting for changes.", subcommand, result);
+            }
+            _ = tokio::signal::ctrl_c() => {
+                if let Some(child) = running.take() {
+                    cancel(child).await;
+                }
+                break;
+            }
+        }
+    }
+
+    println!();
+    println!("[TIP] + Stop watching the project.");
+    println!("[TIP] + [Task End]");
+    println!();
+    true
+}
+
+// Get what to watch for each project type
+async fn watch_spec(project: &projects::Project) -> Option<WatchSpec> {
+    let patterns = |patterns: &[&str]| patterns.iter().map(|p| p.to_string()).collect();
+
+    let spec = if !project.watch.sources.is_empty() {
+        WatchSpec {
+            roots: vec![(".", RecursiveMode::Recursive)],
+            sources: project.watch.sources.clone(),
+        }
+    } else if project.project_type == "workspace" {
+        // The members of a workspace can live anywhere below it
+        WatchSpec {
+            roots: vec![(".", RecursiveMode::Recursive)],
+            sources: patterns(&["**/*.rs", "**/Cargo.toml"]),
+        }
+    } else if project.is_rust_project {
+        WatchSpec {
+            roots: vec![
+                ("src", RecursiveMode::Recursive),
+                (".", RecursiveMode::NonRecursive),
+            ],
+            sources: patterns(&["src/**", "Cargo.toml", "build.rs"]),
+        }
+    } else if matches!(project.project_type.as_str(), "uv" | "poetry" | "pdm") {
+        WatchSpec {
+            roots: vec![(".", RecursiveMode::Recursive)],
+            sources: patterns(&["**/*.py", "pyproject.toml"]),
+        }
+    } else if project.project_type == "pip" {
+        WatchSpec {
+            roots: vec![(".", RecursiveMode::Recursive)],
+            sources: patterns(&["**/*.py", "pyproject.toml", "requirements*.txt"]),
+        }
+    } else if projects::is_environment_type(&project.project_type) {
+        WatchSpec {
+            roots: vec![(".", RecursiveMode::Recursive)],
+            sources: patterns(&[
+                "**/*.{py,f90,f,F90,F}",
+                "fpm.toml",
+                "pyproject.toml",
+                "environment.{yml,yaml}",
+                "pixi.toml",
+            ]),
+        }
+    } else if project.project_type == "cmake" {
+        WatchSpec {
+            roots: vec![(".", RecursiveMode::Recursive)],
+            sources: patterns(&[
+                "**/*.{c,cc,cpp,cxx,h,hh,hpp,hxx}",
+                "**/CMakeLists.txt",
+                "**/*.cmake",
+            ]),
+        }
+    } else if projects::is_node_type(&project.project_type) {
+        WatchSpec {
+            roots: vec![(".", RecursiveMode::Recursive)],
+            sources: patterns(&[
+                "**/*.{js,jsx,mjs,cjs,ts,tsx,mts,cts,vue,svelte,css}",
+                "package.json",
+            ]),
+        }
+    } else if project.project_type == "zig" {
+        WatchSpec {
+            roots: vec![(".", RecursiveMode::Recursive)],
+            sources: patterns(&["**/*.zig", "build.zig.zon"]),
+        }
+    } else if project.project_type == "go" {
+        WatchSpec {
+            roots: vec![(".", RecursiveMode::Recursive)],
+            sources: patterns(&["**/*.go", "go.mod", "go.sum"]),
+        }
+    } else if project.project_type == "fpm" {
+        WatchSpec {
+            roots: vec![(".", RecursiveMode::Recursive)],
+            sources: patterns(&["{src,app,test}/**/*.{f90,f,F90,F}", "fpm.toml"]),
+        }
+    } else if project.is_fortran_project {
+        // The native Flang build compiles the Fortran files next to lox.toml
+        let files = projects::flang::detect_fortran_dependencies().await;
+        WatchSpec {
+            roots: vec![(".", RecursiveMode::NonRecursive)],
+            sources: files.into_iter().map(|(file, _)| file).collect(),
+        }
+    } else {
+        return None;
+    };
+    Some(spec)
+}
+
+// Compile glob patterns, warning about the invalid ones
+fn build_glob_set<'a>(patterns: impl Iterator<Item = &'a str>) -> GlobSet {
+    let mut builder = GlobSetBuilder::new();
+    for pattern in patterns {
+        match Glob::new(pattern) {
+            Ok(glob) => {
+                builder.add(glob);
+            }
+            Err(e) => eprintln!("Warning: Invalid watch pattern `{}`: {}", pattern, e),
+        }
+    }
+    builder.build().unwrap_or_else(|_| GlobSet::empty())
+}
+
+// Get the first source file changed by an event, relative to the project
+fn changed_source(
+    event: &Event,
+    root: &Path,
+    sources: &GlobSet,
+    ignore: &GlobSet,
+) -> Option<String> {
+    if matches!(event.kind, EventKind::Access(_)) {
+        return None;
+    }
+
+    event.paths.iter().find_map(|path| {
+        let relative = path.strip_prefix(root).unwrap_or(path);
+        (sources.is_match(relative) && !ignore.is_match(relative))
+            .then(|| relative.display().to_string())
+    })
+}
+
+// Drain the events until none arrived for the debounce time
+async fn debounce(events: &mut UnboundedReceiver<Event>) {
+    while let Ok(Some(_)) = timeout(DEBOUNCE, events.recv()).await {}
+}
+
+// Run a lox command in the background, or in the foreground of the terminal when it is interactive
+fn start(
+    subcommand: &str,
+    package: Option<&str>,
+    jobs: Option<usize>,
+    interactive: bool,
+) -> Option<Running> {
+    println!();
+
+    // Rerun this lox binary
+    let mut command = Command::new(current_exe().ok()?);
+    if let Some(jobs) = jobs {
+        command.arg("--jobs").arg(jobs.to_string());
+    }
+    command.arg(subcommand);
+    if let Some(package) = package {
+        command.arg("--package").arg(package);
+    }
+
+    // A process group of its own lets a cancel stop the tools it started too. Outside of the
+    // foreground group, reading the terminal stops the command with SIGTTIN, so only an
+    // interactive command keeps the terminal as its input, and gets the terminal below
+    #[cfg(unix)]
+    command.process_group(0);
+    #[cfg(unix)]
+    if !interactive {
+        command.stdin(std::process::Stdio::null());
+    }
+
+    let child = match command.spawn() {
+        Ok(child) => child,
+        Err(e) => {
+            eprintln!("[ERROR] + Failed to execute lox {}: {}", subcommand, e);
+            return None;
+        }
+    };
+    let owns_terminal = interactive && give_terminal(&child);
+    Some(Running {
+        child,
+        owns_terminal,
+    })
+}
+
+// Make the process group of a command the foreground group of the terminal, so it can read it
+//
+// Ctrl-C then interrupts the command instead of the watch, until the terminal is taken back.
+#[cfg(unix)]
+fn give_terminal(child: &Child) -> bool {
+    let Some(pid) = child.id() else {
+        return false;
+    };
+    let group = pid as libc::pid_t;
+    // SAFETY: these calls only read and change the foreground group of the terminal on stdin,
+    // which only happens when lox is that foreground group, and the group is the one `start`
+    // created for the child, which isn't reaped yet
+    unsafe {
+        let is_foreground = libc::isatty(libc::STDIN_FILENO) == 1
+            && libc::tcgetpgrp(libc::STDIN_FILENO) == libc::getpgrp();
+        if !is_foreground || libc::tcsetpgrp(libc::STDIN_FILENO, group) != 0 {
+            return false;
+        }
+        // A read before the handover stopped the command with SIGTTIN, so let it go on
+        libc::kill(-group, libc::SIGCONT);
+    }
+    true
+}
+
+#[cfg(not(unix))]
+fn give_terminal(_child: &Child) -> bool {
+    false
+}
+
+// Make lox the foreground group of the terminal again, once the command is done
+fn take_terminal(running: &Running) {
+    if !running.owns_terminal {
+        return;
+    }
+    // SAFETY: lox is a background group until this call, which would stop it with SIGTTOU
+    // while it sets the foreground group, so the signal is ignored for the call only
+    #[cfg(unix)]
+    unsafe {
+        let previous = libc::signal(libc::SIGTTOU, libc::SIG_IGN);
+        libc::tcsetpgrp(libc::STDIN_FILENO, libc::getpgrp());
+        libc::signal(libc::SIGTTOU, previous);
+    }
+}
+
+// Wait for the running command to exit, or forever when nothing runs
+async fn wait(running: &mut Option<Running>) -> Option<ExitStatus> {
+    match running {
+        Some(running) => running.child.wait().await.ok(),
+        None => std::future::pending().await,
+    }
+}
+
+// Stop a running command and everything it started
+async fn cancel(running: Running) {
+    take_terminal(&running);
+    let mut child = running.child;
+    #[cfg(unix)]
+    if let Some(pid) = child.id() {
+        // Ask the whole process group to stop, and kill it if it takes too long
+        let group = -(pid as libc::pid_t);
+        // SAFETY: `kill` only sends a signal, the group is the one `start` created for the child,
+        // which isn't reaped yet, so its ID can't belong to another group
+        unsafe {
+            libc::kill(group, libc::SIGTERM);
+        }
+        if timeout(CANCEL_GRACE, child.wait()).await.is_err() {
+            // SAFETY: the child didn't exit during the grace period, so the group is still ours
+            unsafe {
+                libc::kill(group, libc::SIGKILL);
+            }
+        }
+    }
+
+    let _ = child.start_kill();
+    let _ = child.wait().await;
+}
@@ -3,12 +3,17 @@ mod projects;
 mod tasks;
 
 use clap::Parser;
+use std::process::ExitCode;
 
 #[derive(Parser, Debug)]
 #[command(author, version, about, long_about = None)]
 struct Args {
     #[command(subcommand)]
     command: Commands,
+
+    /// Number of tasks to run in parallel (defaults to the number of CPUs)
+    #[arg(short = 'j', long = "jobs", global = true)]
+    jobs: Option<usize>,
 }
 
 #[derive(clap::Subcommand, Debug)]
@@ -20,24 +25,156 @@ enum Commands {
         quiet: bool,
     },
     /// Build the project in development mode
-    Dev,
+    Dev {
+        /// Package of the Cargo workspace to use
+        #[arg(short = 'p', long = "package")]
+        package: Option<String>,
+    },
     /// Build the project in release mode
-    Build,
+    Build {
+        /// Package of the Cargo workspace to use
+        #[arg(short = 'p', long = "package")]
+        package: Option<String>,
+    },
     /// Run the project in development mode
-    Dash,
+    Dash {
+        /// Package of the Cargo workspace to use
+        #[arg(short = 'p', long = "package")]
+        package: Option<String>,
+        /// Binary of the Cargo package, or executable of the CMake, Go or Zig project, to run
+        #[arg(long = "bin")]
+        bin: Option<String>,
+        /// Example of the Cargo package to run
+        #[arg(long = "example", conflicts_with = "bin")]
+        example: Option<String>,
+        /// Arguments passed to the program, after `--`
+        #[arg(last = true)]
+        args: Vec<String>,
+    },
     /// Run the project in release mode
-    Run,
+    Run {
+        /// Package of the Cargo workspace to use
+        #[arg(short = 'p', long = "package")]
+        package: Option<String>,
+        /// Binary of the Cargo package, or executable of the CMake, Go or Zig project, to run
+        #[arg(long = "bin")]
+        bin: Option<String>,
+        /// Example of the Cargo package to run
+        #[arg(long = "example", conflicts_with = "bin")]
+        example: Option<String>,
+        /// Script of the uv project to run, from `[project.scripts]`
+        script: Option<String>,
+        /// Arguments passed to the program, after `--`
+        #[arg(last = true)]
+        args: Vec<String>,
+    },
+    /// Run the tests of the project
+    Test {
+        /// Package of the Cargo workspace to use
+        #[arg(short = 'p', long = "package")]
+        package: Option<String>,
+    },
+    /// Format the project with the `fmt` command of lox.toml
+    Fmt {
+        /// Check the formatting without modifying files
+        #[arg(long = "check")]
+        check: bool,
+    },
+    /// Lint the project with the `lint` command of lox.toml
+    Lint {
+        /// Apply the suggested fixes
+        #[arg(long = "fix")]
+        fix: bool,
+    },
+    /// Remove the build outputs of the project
+    Clean {
+        /// Only remove the development build outputs
+        #[arg(long = "dev", conflicts_with_all = ["release", "all"])]
+        dev: bool,
+        /// Only remove the release build outputs
+        #[arg(long = "release", conflicts_with = "all")]
+        release: bool,
+        /// Also remove caches and environments
+        #[arg(long = "all")]
+        all: bool,
+        /// List what would be removed without deleting anything
+        #[arg(long = "dry-run")]
+        dry_run: bool,
+    },
+    /// Rerun a command when the project sources change
+    Watch {
+        /// Command to rerun
+        #[arg(value_enum, default_value = "dash")]
+        command: commands::watch::WatchCommand,
+        /// Package of the Cargo workspace to use
+        #[arg(short = 'p', long = "package")]
+        package: Option<String>,
+    },
+    /// Run a built-in or lox.toml task
+    Task {
+        /// ID of the task to run
+        name: Option<String>,
+        /// List the available tasks
+        #[arg(short = 'l', long = "list")]
+        list: bool,
+    },
 }
 
 #[tokio::main]
-async fn main() {
+async fn main() -> ExitCode {
     let args = Args::parse();
 
-    match args.command {
+    if let Some(jobs) = args.jobs {
+        tasks::set_jobs(jobs);
+    }
+
+    let success = match args.command {
         Commands::Doctor { quiet } => commands::doctor::run(quiet).await,
-        Commands::Dev => commands::dev::run().await,
-        Commands::Build => commands::build::run().await,
-        Commands::Dash => commands::dash::run().await,
-        Commands::Run => commands::run::run().await,
+        Commands::Dev { package } => commands::dev::run(package).await,
+        Commands::Build { package } => commands::build::run(package).await,
+        Commands::Dash {
+            package,
+            bin,
+            example,
+            args,
+        } => commands::dash::run(package, bin, example, args).await,
+        Commands::Run {
+            package,
+            bin,
+            example,
+            script,
+            args,
+        } => commands::run::run(package, bin, example, script, args).await,
+        Commands::Test { package } => commands::test::run(package).await,
+        Commands::Fmt { check } => commands::fmt::run(check).await,
+        Commands::Lint { fix } => commands::lint::run(fix).await,
+        Commands::Clean {
+            dev,
+            release,
+            all,
+            dry_run,
+        } => {
+            let mode = if dev {
+                commands::clean::CleanMode::Dev
+            } else if release {
+                commands::clean::CleanMode::Release
+            } else if all {
+                commands::clean::CleanMode::All
+            } else {
+                commands::clean::CleanMode::Build
+            };
+            commands::clean::run(mode, dry_run).await
+        }
+        Commands::Watch { command, package } => {
+            commands::watch::run(command, package, args.jobs).await
+        }
+        Commands::Task { name, list } => commands::task::run(name, list).await,
+    };
+
+    // Exit nonzero when a step failed, so lox can be used in CI and git hooks
+    if success {
+        ExitCode::SUCCESS
+    } else {
+        ExitCode::FAILURE
     }
 }
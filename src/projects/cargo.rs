@@ -1,37 +1,258 @@
-use super::{BuildCommands, Project, RunCommands};
+use super::{
+    BuildCommands, Pipelines, Project, ProjectCommands, RunCommands, WatchConfig, command_path,
+    directory_name, slash_path,
+};
+use serde::Deserialize;
+use std::env::consts::EXE_SUFFIX;
+use std::path::{Path, PathBuf};
 use tokio::fs::{metadata, read_to_string};
+use tokio::process::Command;
+
+/// The parts of a Cargo.toml lox reads
+#[derive(Debug, Default, Deserialize)]
+struct CargoManifest {
+    package: Option<PackageSection>,
+    workspace: Option<WorkspaceSection>,
+    lib: Option<toml::Value>,
+    #[serde(default)]
+    bin: Vec<toml::Value>,
+}
+
+#[derive(Debug, Deserialize)]
+struct PackageSection {
+    name: String,
+    /// A version string, or `{ workspace = true }` in a workspace member
+    version: Option<toml::Value>,
+}
+
+#[derive(Debug, Default, Deserialize)]
+struct WorkspaceSection {
+    package: Option<WorkspacePackage>,
+}
+
+#[derive(Debug, Deserialize)]
+struct WorkspacePackage {
+    version: Option<String>,
+}
+
+/// A package of a Cargo workspace
+#[derive(Debug, Clone)]
+pub struct CargoMember {
+    pub name: String,
+    pub version: String,
+    /// Name of the first binary of the package, `None` for a library
+    pub binary: Option<String>,
+}
+
+impl CargoMember {
+    /// The kind of package, as shown by `doctor`
+    pub fn kind(&self) -> &'static str {
+        if self.binary.is_some() { "bin" } else { "lib" }
+    }
+}
+
+/// Output of `cargo metadata --format-version 1 --no-deps`
+#[derive(Debug, Deserialize)]
+pub struct CargoMetadata {
+    /// Every package of the workspace, or the only package of the project
+    pub packages: Vec<MetadataPackage>,
+    /// Follows `CARGO_TARGET_DIR` and `[build] target-dir`
+    pub target_directory: PathBuf,
+    pub workspace_root: PathBuf,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct MetadataPackage {
+    pub name: String,
+    pub version: String,
+    pub manifest_path: PathBuf,
+    pub targets: Vec<MetadataTarget>,
+    pub default_run: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct MetadataTarget {
+    pub name: String,
+    pub kind: Vec<String>,
+}
+
+impl CargoMetadata {
+    /// The package of the root Cargo.toml, `None` for a virtual workspace
+    pub fn root_package(&self) -> Option<&MetadataPackage> {
+        let root_manifest = self.workspace_root.join("Cargo.toml");
+        self.packages
+            .iter()
+            .find(|package| package.manifest_path == root_manifest)
+    }
+
+    /// Path of a binary built with a profile (`debug` or `release`), relative when possible
+    pub fn binary_path(&self, profile: &str, binary: &str) -> String {
+        command_path(&display_path(
+            &self
+                .target_directory
+                .join(profile)
+                .join(format!("{}{}", binary, EXE_SUFFIX)),
+        ))
+    }
+
+    /// The path of an example built with a profile
+    pub fn example_path(&self, profile: &str, example: &str) -> String {
+        command_path(&display_path(
+            &self
+                .target_directory
+                .join(profile)
+                .join("examples")
+                .join(format!("{}{}", example, EXE_SUFFIX)),
+        ))
+    }
+
+    /// The target directory, relative when possible
+    pub fn target_dir(&self) -> String {
+        slash_path(&display_path(&self.target_directory))
+    }
+
+    /// The binary a run command saved in lox.toml points to, when it is a bare binary path
+    ///
+    /// Saved paths go stale when the target directory moves, and older versions of lox guessed
+    /// `./target/debug/my_app` for a `my-app` package, so both are matched by binary name.
+    pub fn saved_binary(&self, command: &str) -> Option<&str> {
+        let words = shlex::split(command)?;
+        let [path] = words.as_slice() else {
+            return None;
+        };
+        let path = Path::new(path);
+        let profile = path.parent()?.file_name()?;
+        if profile != "debug" && profile != "release" {
+            return None;
+        }
+        let file_name = path.file_name()?.to_str()?;
+        let name = file_name.strip_suffix(EXE_SUFFIX).unwrap_or(file_name);
+        self.packages
+            .iter()
+            .flat_map(|package| package.binaries())
+            .find(|binary| *binary == name || binary.replace('-', "_") == name)
+    }
+}
+
+/// Show a path relative to the project when it is inside of it
+fn display_path(path: &Path) -> PathBuf {
+    let relative = std::env::current_dir()
+        .ok()
+        .and_then(|dir| path.strip_prefix(dir).ok().map(Path::to_path_buf));
+    match relative {
+        Some(relative) => Path::new(".").join(relative),
+        None => path.to_path_buf(),
+    }
+}
+
+impl MetadataPackage {
+    /// Names of the binary targets of the package
+    pub fn binaries(&self) -> impl Iterator<Item = &str> {
+        self.targets
+            .iter()
+            .filter(|target| target.kind.iter().any(|kind| kind == "bin"))
+            .map(|target| target.name.as_str())
+    }
+
+    /// The names of the example targets
+    pub fn examples(&self) -> impl Iterator<Item = &str> {
+        self.targets
+            .iter()
+            .filter(|target| target.kind.iter().any(|kind| kind == "example"))
+            .map(|target| target.name.as_str())
+    }
+
+    /// The binary `cargo run` picks: `default-run`, or the only binary
+    pub fn default_binary(&self) -> Option<&str> {
+        if let Some(default_run) = &self.default_run {
+            return Some(default_run);
+        }
+        let mut binaries = self.binaries();
+        match (binaries.next(), binaries.next()) {
+            (Some(binary), None) => Some(binary),
+            _ => None,
+        }
+    }
+
+    /// Whether the package has a library target
+    pub fn is_library(&self) -> bool {
+        self.targets.iter().any(|target| {
+            target
+                .kind
+                .iter()
+                .any(|kind| kind.ends_with("lib") || kind == "proc-macro")
+        })
+    }
+}
+
+/// Ask cargo about the packages, targets and target directory of the project
+pub async fn cargo_metadata() -> Option<CargoMetadata> {
+    let output = Command::new("cargo")
+        .args([
+            "metadata",
+            "--format-version",
+            "1",
+            "--no-deps",
+            "--offline",
+        ])
+        .output()
+        .await
+        .ok()?;
+    if !output.status.success() {
+        return None;
+    }
+    serde_json::from_slice(&output.stdout).ok()
+}
 
 pub async fn detect_cargo_project() -> Option<Project> {
     // Check if it's a Rust project (has Cargo.toml)
-    if !metadata("Cargo.toml").await.is_ok() {
+    if metadata("Cargo.toml").await.is_err() {
         return None;
     }
 
+    let manifest = read_manifest(Path::new(".")).await.unwrap_or_default();
+    let workspace_version = manifest
+        .workspace
+        .as_ref()
+        .and_then(|workspace| workspace.package.as_ref())
+        .and_then(|package| package.version.clone());
+
     let mut project_type = String::from("unknown");
     let mut project_name = String::from("unknown");
     let mut project_version = String::from("unknown");
     let mut is_library = false;
 
-    if let Ok(cargo_content) = read_to_string("Cargo.toml").await {
-        for line in cargo_content.lines() {
-            if line.starts_with("name = ") {
-                if let Some((_, rest)) = line.split_once('"') {
-                    if let Some((name, _)) = rest.split_once('"') {
-                        project_name = name.to_string();
-                    }
-                }
-            } else if line.starts_with("version = ") {
-                if let Some((_, rest)) = line.split_once('"') {
-                    if let Some((version, _)) = rest.split_once('"') {
-                        project_version = version.to_string();
-                    }
-                }
-            } else if line.starts_with("[lib]") {
-                project_type = String::from("library(lib)");
-                is_library = true;
-            } else if line.starts_with("[[bin]]") {
-                project_type = String::from("app(bin)");
-            }
+    if let Some(package) = &manifest.package {
+        project_name = package.name.clone();
+        project_version = package_version(package, workspace_version.as_deref());
+
+        if !manifest.bin.is_empty() {
+            project_type = String::from("app(bin)");
+        } else if manifest.lib.is_some() {
+            project_type = String::from("library(lib)");
+            is_library = true;
+        }
+    } else if manifest.workspace.is_some() {
+        // A virtual workspace has no package of its own, so it is named after its directory
+        project_type = String::from("workspace");
+        if let Some(name) = directory_name() {
+            project_name = name;
+        }
+        if let Some(version) = workspace_version {
+            project_version = version;
+        }
+    }
+
+    // Targets resolved by cargo take precedence over the guesses from the manifest
+    let cargo = cargo_metadata().await;
+    let root_package = cargo.as_ref().and_then(CargoMetadata::root_package);
+    if let Some(package) = root_package {
+        if package.binaries().next().is_some() {
+            project_type = String::from("app(bin)");
+            is_library = false;
+        } else if package.is_library() {
+            project_type = String::from("library(lib)");
+            is_library = true;
         }
     }
 
@@ -53,29 +274,101 @@ pub async fn detect_cargo_project() -> Option<Project> {
         release: String::from("cargo build --release"),
     };
 
-    // Determine run commands based on project type and binary name
-    let binary_name = project_name.replace('-', "_");
-    let run_commands = if !is_library {
-        RunCommands {
-            dev: format!("./target/debug/{}", binary_name),
-            release: format!("./target/release/{}", binary_name),
-        }
-    } else {
-        RunCommands {
+    // Determine run commands from the binary `cargo run` would pick, the binaries of a
+    // workspace or of a package with several of them are picked when running instead
+    let default_binary = match (&cargo, root_package) {
+        (Some(cargo), Some(package)) => package.default_binary().map(|binary| {
+            (
+                cargo.binary_path("debug", binary),
+                cargo.binary_path("release", binary),
+            )
+        }),
+        // Without cargo, fall back to the binary named after the package
+        (None, _) if !is_library && project_type != "workspace" => Some((
+            format!("./target/debug/{}{}", project_name, EXE_SUFFIX),
+            format!("./target/release/{}{}", project_name, EXE_SUFFIX),
+        )),
+        _ => None,
+    };
+    let run_commands = match default_binary {
+        Some((dev, release)) => RunCommands { dev, release },
+        None => RunCommands {
             dev: String::from("unknown"),
             release: String::from("unknown"),
-        }
+        },
     };
 
+    let commands = ProjectCommands::defaults(&project_type);
+
     Some(Project {
         project_type,
         name: project_name,
         version: project_version,
-        is_library,
         build_commands,
         run_commands,
+        commands,
         is_rust_project: true,
         is_uv_project: false,
         is_fortran_project: false,
+        user_tasks: Vec::new(),
+        pipelines: Pipelines::default(),
+        watch: WatchConfig::default(),
     })
 }
+
+/// List the packages of the Cargo workspace, empty when the project is not a workspace
+pub async fn detect_workspace_members() -> Vec<CargoMember> {
+    let Some(manifest) = read_manifest(Path::new(".")).await else {
+        return Vec::new();
+    };
+    if manifest.workspace.is_none() {
+        return Vec::new();
+    }
+
+    // cargo resolves the member globs, binary names and inherited versions itself
+    let Some(cargo) = cargo_metadata().await else {
+        eprintln!("Warning: `cargo metadata` failed, so the workspace members are unknown");
+        return Vec::new();
+    };
+    cargo
+        .packages
+        .iter()
+        .map(|package| CargoMember {
+            name: package.name.clone(),
+            version: package.version.clone(),
+            binary: package
+                .default_binary()
+                .or_else(|| package.binaries().next())
+                .map(String::from),
+        })
+        .collect()
+}
+
+/// Read and parse the Cargo.toml of a directory
+async fn read_manifest(dir: &Path) -> Option<CargoManifest> {
+    let content = read_to_string(dir.join("Cargo.toml")).await.ok()?;
+    match toml::from_str(&content) {
+        Ok(manifest) => Some(manifest),
+        Err(e) => {
+            eprintln!(
+                "Warning: Failed to parse `{}`: {}",
+                dir.join("Cargo.toml").display(),
+                e.message().trim()
+            );
+            None
+        }
+    }
+}
+
+/// Get the version of a package, following `version.workspace = true`
+fn package_version(package: &PackageSection, workspace_version: Option<&str>) -> String {
+    match &package.version {
+        Some(toml::Value::String(version)) => version.clone(),
+        Some(toml::Value::Table(table)) if table.contains_key("workspace") => {
+            workspace_version.unwrap_or("unknown").to_string()
+        }
+        // Packages without a version default to 0.0.0
+        None => String::from("0.0.0"),
+        Some(_) => String::from("unknown"),
+    }
+}
@@ -0,0 +1,236 @@
+use super::{
+    BuildCommands, DEV_DIR, Executable, Project, RELEASE_DIR, RunCommands, command_path,
+    directory_name,
+};
+use serde::Deserialize;
+use std::path::Path;
+use std::process::Stdio;
+use tokio::fs::{create_dir_all, read_dir, read_to_string, write};
+use tokio::process::Command;
+
+/// The index of a CMake File API reply, the parts lox reads
+#[derive(Debug, Deserialize)]
+struct ReplyIndex {
+    objects: Vec<ReplyObject>,
+}
+
+#[derive(Debug, Deserialize)]
+struct ReplyObject {
+    kind: String,
+    #[serde(rename = "jsonFile")]
+    json_file: String,
+}
+
+#[derive(Debug, Deserialize)]
+struct Codemodel {
+    configurations: Vec<CodemodelConfiguration>,
+}
+
+#[derive(Debug, Deserialize)]
+struct CodemodelConfiguration {
+    name: String,
+    targets: Vec<CodemodelTarget>,
+}
+
+#[derive(Debug, Deserialize)]
+struct CodemodelTarget {
+    name: String,
+    #[serde(rename = "jsonFile")]
+    json_file: String,
+}
+
+#[derive(Debug, Deserialize)]
+struct TargetObject {
+    #[serde(rename = "type")]
+    kind: String,
+    #[serde(default)]
+    artifacts: Vec<Artifact>,
+}
+
+#[derive(Debug, Deserialize)]
+struct Artifact {
+    path: String,
+}
+
+/// Detect a C or C++ project built with CMake
+pub async fn detect_cmake_project() -> Option<Project> {
+    let content = read_to_string("CMakeLists.txt").await.ok()?;
+    let (name, version) = project_call(&content).unzip();
+
+    let name = name
+        .or_else(directory_name)
+        .unwrap_or_else(|| String::from("unknown"));
+
+    // The executables are only known once the build directory is configured
+    let run_dev = default_executable(DEV_DIR).await;
+    let run_release = default_executable(RELEASE_DIR).await;
+
+    Some(Project::new(
+        "cmake",
+        name,
+        version.flatten().unwrap_or_else(|| String::from("unknown")),
+        BuildCommands {
+            dev: format!(
+                "cmake {} && cmake {}",
+                configure_args(false).join(" "),
+                build_args(false).join(" ")
+            ),
+            release: format!(
+                "cmake {} && cmake {}",
+                configure_args(true).join(" "),
+                build_args(true).join(" ")
+            ),
+        },
+        RunCommands {
+            dev: run_dev,
+            release: run_release,
+        },
+    ))
+}
+
+/// The build directory of a profile
+pub fn build_dir(release: bool) -> &'static str {
+    if release { RELEASE_DIR } else { DEV_DIR }
+}
+
+/// The CMake build type of a profile
+fn build_type(release: bool) -> &'static str {
+    if release { "Release" } else { "Debug" }
+}
+
+/// The arguments of `cmake` that configure the build directory of a profile
+pub fn configure_args(release: bool) -> Vec<String> {
+    vec![
+        String::from("-S"),
+        String::from("."),
+        String::from("-B"),
+        build_dir(release).to_string(),
+        format!("-DCMAKE_BUILD_TYPE={}", build_type(release)),
+    ]
+}
+
+/// The arguments of `cmake` that build a profile, `--config` picks it for multi-config generators
+pub fn build_args(release: bool) -> Vec<String> {
+    vec![
+        String::from("--build"),
+        build_dir(release).to_string(),
+        String::from("--config"),
+        build_type(release).to_string(),
+    ]
+}
+
+/// Ask the CMake File API for the targets, answered by the next configure
+pub async fn write_query(dir: &str) -> std::io::Result<()> {
+    let query = Path::new(dir).join(".cmake/api/v1/query");
+    create_dir_all(&query).await?;
+    write(query.join("codemodel-v2"), "").await
+}
+
+/// Configure the build directory of a profile without showing the output
+pub async fn configure(release: bool) -> bool {
+    if write_query(build_dir(release)).await.is_err() {
+        return false;
+    }
+    Command::new("cmake")
+        .args(configure_args(release))
+        .stdout(Stdio::null())
+        .stderr(Stdio::null())
+        .status()
+        .await
+        .is_ok_and(|status| status.success())
+}
+
+/// Find the executable targets in the File API reply of a build directory
+///
+/// Returns `None` when the build directory has no reply, because it is not configured yet.
+pub async fn executables(dir: &str) -> Option<Vec<Executable>> {
+    let reply = Path::new(dir).join(".cmake/api/v1/reply");
+
+    // Every configure writes a new index, the latest one sorts last
+    let mut indexes = Vec::new();
+    let mut entries = read_dir(&reply).await.ok()?;
+    while let Ok(Some(entry)) = entries.next_entry().await {
+        let name = entry.file_name().to_string_lossy().to_string();
+        if name.starts_with("index-") && name.ends_with(".json") {
+            indexes.push(name);
+        }
+    }
+    indexes.sort();
+    let index: ReplyIndex = read_json(&reply.join(indexes.pop()?)).await?;
+
+    let codemodel = index
+        .objects
+        .iter()
+        .find(|object| object.kind == "codemodel")?;
+    let codemodel: Codemodel = read_json(&reply.join(&codemodel.json_file)).await?;
+
+    // Multi-config generators describe every build type, the others only the configured one
+    let build_type = build_type(dir == RELEASE_DIR);
+    let configuration = codemodel
+        .configurations
+        .iter()
+        .find(|configuration| configuration.name == build_type)
+        .or_else(|| codemodel.configurations.first())?;
+
+    let mut executables = Vec::new();
+    for target in &configuration.targets {
+        let Some(object) = read_json::<TargetObject>(&reply.join(&target.json_file)).await else {
+            continue;
+        };
+        if object.kind != "EXECUTABLE" {
+            continue;
+        }
+        if let Some(artifact) = object.artifacts.first() {
+            let path = Path::new(dir).join(&artifact.path);
+            let path = if path.is_absolute() {
+                command_path(&path)
+            } else {
+                command_path(&Path::new(".").join(path))
+            };
+            executables.push(Executable {
+                name: target.name.clone(),
+                path,
+            });
+        }
+    }
+    executables.sort_by(|a, b| a.name.cmp(&b.name));
+    Some(executables)
+}
+
+/// The executable `lox run` runs without `--bin`, when the project has only one
+async fn default_executable(dir: &str) -> String {
+    match executables(dir).await.as_deref() {
+        Some([executable]) => executable.path.clone(),
+        _ => String::from("unknown"),
+    }
+}
+
+async fn read_json<T: for<'de> Deserialize<'de>>(path: &Path) -> Option<T> {
+    let content = read_to_string(path).await.ok()?;
+    serde_json::from_str(&content).ok()
+}
+
+/// Read the name and `VERSION` of the `project()` call in CMakeLists.txt
+fn project_call(content: &str) -> Option<(String, Option<String>)> {
+    // ASCII lowercasing keeps the byte offsets of the original content
+    let lower = content.to_ascii_lowercase();
+    let open = lower.match_indices("project").find_map(|(index, _)| {
+        let line_start = lower[..index].rsplit('\n').next().unwrap_or_default();
+        let rest = &lower[index + "project".len()..];
+        let call = rest.trim_start();
+        (line_start.trim().is_empty() && call.starts_with('('))
+            .then(|| index + "project".len() + (rest.len() - call.len()))
+    })?;
+    let close = open + content[open..].find(')')?;
+
+    let words: Vec<&str> = content[open + 1..close]
+        .split_whitespace()
+        .map(|word| word.trim_matches('"'))
+        .collect();
+    let name = words.first()?.to_string();
+    let version = words
+        .windows(2)
+        .find(|pair| pair[0].eq_ignore_ascii_case("VERSION"))
+        .map(|pair| pair[1].to_string());
+    Some((name, version))
+}
@@ -0,0 +1,242 @@
+use super::python::{PyProject, detect_entry_points};
+use super::{BuildCommands, Project, RunCommands, directory_name};
+use serde::Deserialize;
+use std::collections::BTreeMap;
+use tokio::fs::{metadata, read_to_string};
+use tokio::process::Command;
+
+/// The conda environment files, in the order they are looked for
+pub const ENVIRONMENT_FILES: [&str; 2] = ["environment.yml", "environment.yaml"];
+
+/// The parts of a pixi.toml lox reads
+#[derive(Debug, Default, Deserialize)]
+struct PixiManifest {
+    project: Option<PixiProject>,
+    workspace: Option<PixiProject>,
+    #[serde(default)]
+    tasks: BTreeMap<String, toml::Value>,
+}
+
+#[derive(Debug, Default, Deserialize)]
+struct PixiProject {
+    name: Option<String>,
+    version: Option<String>,
+}
+
+/// The commands of the project inside the environment, before they are wrapped
+#[derive(Debug)]
+pub struct InnerCommands {
+    pub build_dev: String,
+    pub build_release: String,
+    pub run_dev: String,
+    pub run_release: String,
+}
+
+/// Detect a project managed with a conda `environment.yml` or a `pixi.toml`
+pub async fn detect_conda_project(pyproject: Option<&PyProject>) -> Option<Project> {
+    let project_type = detect_environment_type().await?;
+    let (name, version) = if project_type == "pixi" {
+        let manifest = read_pixi_manifest().await;
+        let project = manifest.workspace.or(manifest.project).unwrap_or_default();
+        (project.name, project.version)
+    } else {
+        (
+            pyproject.and_then(PyProject::name),
+            pyproject.and_then(PyProject::version),
+        )
+    };
+
+    let name = name
+        .or_else(directory_name)
+        .unwrap_or_else(|| String::from("unknown"));
+
+    let (build_commands, run_commands) = environment_commands(project_type, pyproject).await;
+
+    Some(Project::new(
+        project_type,
+        name,
+        version.unwrap_or_else(|| String::from("unknown")),
+        build_commands,
+        run_commands,
+    ))
+}
+
+/// Find which tool manages the environment, `pixi` or `conda`
+pub async fn detect_environment_type() -> Option<&'static str> {
+    if metadata("pixi.toml").await.is_ok() {
+        Some("pixi")
+    } else if find_environment_file().await.is_some() {
+        Some("conda")
+    } else {
+        None
+    }
+}
+
+/// Get the build and run commands of the project, wrapped to run inside the environment
+pub async fn environment_commands(
+    project_type: &str,
+    pyproject: Option<&PyProject>,
+) -> (BuildCommands, RunCommands) {
+    let inner = detect_inner_commands(pyproject).await;
+    let prefix = environment_prefix(project_type).await;
+    let wrap = |command: &str| {
+        if command == "unknown" {
+            command.to_string()
+        } else {
+            format!("{} {}", prefix, command)
+        }
+    };
+
+    // pixi tasks named `build` and `start` replace the detected commands
+    let tasks = if project_type == "pixi" {
+        read_pixi_manifest().await.tasks
+    } else {
+        BTreeMap::new()
+    };
+    let build_release = if tasks.contains_key("build") {
+        String::from("pixi run build")
+    } else {
+        wrap(&inner.build_release)
+    };
+    let run_release = if tasks.contains_key("start") {
+        String::from("pixi run start")
+    } else {
+        wrap(&inner.run_release)
+    };
+
+    (
+        BuildCommands {
+            dev: wrap(&inner.build_dev),
+            release: build_release,
+        },
+        RunCommands {
+            dev: wrap(&inner.run_dev),
+            release: run_release,
+        },
+    )
+}
+
+/// Find the conda environment file of the project
+pub async fn find_environment_file() -> Option<&'static str> {
+    for file in ENVIRONMENT_FILES {
+        if metadata(file).await.is_ok() {
+            return Some(file);
+        }
+    }
+    None
+}
+
+/// Read the `name:` of the conda environment file
+pub async fn environment_name() -> Option<String> {
+    let content = read_to_string(find_environment_file().await?).await.ok()?;
+    content.lines().find_map(|line| {
+        let name = line.strip_prefix("name:")?.trim().trim_matches(['"', '\'']);
+        (!name.is_empty()).then(|| name.to_string())
+    })
+}
+
+/// The command prefix that runs a program inside the environment
+pub async fn environment_prefix(project_type: &str) -> String {
+    if project_type == "pixi" {
+        return String::from("pixi run");
+    }
+    match environment_name().await {
+        Some(name) => format!("conda run -n {} --no-capture-output", name),
+        None => String::from("conda run --no-capture-output"),
+    }
+}
+
+/// Find where the environment lives on disk
+pub async fn environment_location(project_type: &str) -> Option<String> {
+    if project_type == "pixi" {
+        let default = ".pixi/envs/default";
+        return metadata(default).await.is_ok().then(|| default.to_string());
+    }
+
+    // The prefix of the named environment, asked to the Python inside it
+    let name = environment_name().await?;
+    let output = Command::new("conda")
+        .args([
+            "run",
+            "-n",
+            &name,
+            "python",
+            "-c",
+            "import sys; print(sys.prefix)",
+        ])
+        .output()
+        .await
+        .ok()?;
+    let location = String::from_utf8_lossy(&output.stdout).trim().to_string();
+    (output.status.success() && !location.is_empty()).then_some(location)
+}
+
+/// Get the first line a program prints for `--version` inside the environment
+pub async fn environment_tool_version(project_type: &str, program: &str) -> Option<String> {
+    let prefix = environment_prefix(project_type).await;
+    let words = shlex::split(&prefix)?;
+    let (launcher, args) = words.split_first()?;
+    let output = Command::new(launcher)
+        .args(args)
+        .args([program, "--version"])
+        .output()
+        .await
+        .ok()?;
+    if !output.status.success() {
+        return None;
+    }
+
+    // Python prints its version to stderr before 3.4
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    stdout
+        .lines()
+        .chain(stderr.lines())
+        .find(|line| !line.trim().is_empty())
+        .map(|line| line.trim().to_string())
+}
+
+/// Detect the commands of an fpm or Python project living in the environment
+pub async fn detect_inner_commands(pyproject: Option<&PyProject>) -> InnerCommands {
+    if metadata("fpm.toml").await.is_ok() {
+        return InnerCommands {
+            build_dev: String::from("fpm build"),
+            build_release: String::from("fpm build --profile release"),
+            run_dev: String::from("fpm run"),
+            run_release: String::from("fpm run --profile release"),
+        };
+    }
+
+    // A Python package is only built for release, in dev mode the environment is enough
+    let has_python_package = pyproject.is_some() || metadata("setup.py").await.is_ok();
+    let build_release = if has_python_package {
+        String::from("python -m build")
+    } else {
+        String::from("unknown")
+    };
+    let run = match detect_entry_points(pyproject).await.default_target() {
+        Some(target) if target.ends_with(".py") => format!("python {}", target),
+        Some(target) => target,
+        None => String::from("unknown"),
+    };
+
+    InnerCommands {
+        build_dev: String::from("unknown"),
+        build_release,
+        run_dev: run.clone(),
+        run_release: run,
+    }
+}
+
+async fn read_pixi_manifest() -> PixiManifest {
+    let Ok(content) = read_to_string("pixi.toml").await else {
+        return PixiManifest::default();
+    };
+    match toml::from_str(&content) {
+        Ok(manifest) => manifest,
+        Err(e) => {
+            eprintln!("Warning: Failed to parse pixi.toml: {}", e.message());
+            PixiManifest::default()
+        }
+    }
+}
@@ -0,0 +1,380 @@
+use crate::projects::{
+    BuildCommands, Pipelines, Project, ProjectCommands, RunCommands, UserTask, WatchConfig,
+    is_rust_type,
+};
+use serde::Deserialize;
+use std::collections::BTreeMap;
+use std::fmt;
+use toml::de::{DeTable, DeValue};
+
+/// Name of the lox configuration file
+pub const CONFIG_FILE: &str = "lox.toml";
+
+/// Typed schema of `lox.toml`
+#[derive(Debug, Default, Deserialize)]
+pub struct LoxConfig {
+    #[serde(default)]
+    pub project: ProjectSection,
+    #[serde(default)]
+    pub tasks: BTreeMap<String, TaskSection>,
+    #[serde(default)]
+    pub pipeline: Pipelines,
+    #[serde(default)]
+    pub watch: WatchConfig,
+}
+
+/// The `[project]` table
+#[derive(Debug, Default, Deserialize)]
+pub struct ProjectSection {
+    #[serde(rename = "type")]
+    pub project_type: Option<String>,
+    pub name: Option<String>,
+    pub version: Option<String>,
+    #[serde(default)]
+    pub build: CommandPair,
+    #[serde(default)]
+    pub run: CommandPair,
+    #[serde(default)]
+    pub commands: CommandsSection,
+}
+
+/// The `[project.build]` and `[project.run]` tables
+#[derive(Debug, Default, Deserialize)]
+pub struct CommandPair {
+    pub dev: Option<String>,
+    pub release: Option<String>,
+}
+
+/// The `[project.commands]` table
+#[derive(Debug, Default, Deserialize)]
+pub struct CommandsSection {
+    pub fmt: Option<String>,
+    pub fmt_check: Option<String>,
+    pub lint: Option<String>,
+    pub lint_fix: Option<String>,
+    pub dependency: Option<String>,
+}
+
+/// A `[tasks.<name>]` table
+#[derive(Debug, Deserialize)]
+pub struct TaskSection {
+    pub cmd: String,
+    #[serde(default)]
+    pub args: Vec<String>,
+    pub cwd: Option<String>,
+    #[serde(default)]
+    pub env: BTreeMap<String, String>,
+    pub description: Option<String>,
+    #[serde(default)]
+    pub depends_on: Vec<String>,
+}
+
+/// Error raised while loading `lox.toml`
+#[derive(Debug)]
+pub enum ConfigError {
+    /// The file could not be read
+    Io(std::io::Error),
+    /// The file is not valid TOML or doesn't match the schema
+    Invalid {
+        line: usize,
+        column: usize,
+        message: String,
+    },
+}
+
+impl fmt::Display for ConfigError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ConfigError::Io(e) => write!(f, "{}: {}", CONFIG_FILE, e),
+            ConfigError::Invalid {
+                line,
+                column,
+                message,
+            } => write!(f, "{}:{}:{}: {}", CONFIG_FILE, line, column, message),
+        }
+    }
+}
+
+impl LoxConfig {
+    /// Parse and validate the content of `lox.toml`, warning about unknown keys
+    pub fn parse(content: &str) -> Result<Self, ConfigError> {
+        let config: LoxConfig = toml::from_str(content).map_err(|e| {
+            let (line, column) = e
+                .span()
+                .map(|span| line_column(content, span.start))
+                .unwrap_or((1, 1));
+            ConfigError::Invalid {
+                line,
+                column,
+                message: e.message().trim().to_string(),
+            }
+        })?;
+
+        for (key, line, column) in find_unknown_keys(content) {
+            eprintln!(
+                "Warning: {}:{}:{}: unknown key `{}`",
+                CONFIG_FILE, line, column, key
+            );
+        }
+
+        Ok(config)
+    }
+
+    /// The `type` of the project, "unknown" when it is missing
+    pub fn project_type(&self) -> &str {
+        self.project.project_type.as_deref().unwrap_or("unknown")
+    }
+
+    /// Convert the configuration into the `Project` shared by every command
+    ///
+    /// `defaults` are the commands of the project type, from `ProjectCommands::detect`.
+    pub fn into_project(self, defaults: ProjectCommands) -> Project {
+        let unknown = || String::from("unknown");
+        let project_type = self.project.project_type.unwrap_or_else(unknown);
+
+        // Missing commands fall back to the defaults of the project type, but the check and
+        // fix variants of a default command don't apply to a command the user replaced
+        let configured = self.project.commands;
+        let fmt_check = configured.fmt_check.or_else(|| {
+            configured
+                .fmt
+                .as_ref()
+                .is_none_or(|fmt| *fmt == defaults.fmt)
+                .then(|| defaults.fmt_check.clone())
+                .flatten()
+        });
+        let lint_fix = configured.lint_fix.or_else(|| {
+            configured
+                .lint
+                .as_ref()
+                .is_none_or(|lint| *lint == defaults.lint)
+                .then(|| defaults.lint_fix.clone())
+                .flatten()
+        });
+        let commands = ProjectCommands {
+            fmt: configured.fmt.unwrap_or(defaults.fmt),
+            fmt_check,
+            lint: configured.lint.unwrap_or(defaults.lint),
+            lint_fix,
+            dependency: configured.dependency.unwrap_or(defaults.dependency),
+        };
+
+        Project {
+            is_rust_project: is_rust_type(&project_type),
+            is_uv_project: project_type == "uv",
+            is_fortran_project: project_type == "llvm-f" || project_type == "fpm",
+            name: self.project.name.unwrap_or_else(unknown),
+            version: self.project.version.unwrap_or_else(unknown),
+            build_commands: BuildCommands {
+                dev: self.project.build.dev.unwrap_or_else(unknown),
+                release: self.project.build.release.unwrap_or_else(unknown),
+            },
+            run_commands: RunCommands {
+                dev: self.project.run.dev.unwrap_or_else(unknown),
+                release: self.project.run.release.unwrap_or_else(unknown),
+            },
+            commands,
+            user_tasks: self
+                .tasks
+                .into_iter()
+                .map(|(name, task)| UserTask {
+                    name,
+                    cmd: task.cmd,
+                    args: task.args,
+                    cwd: task.cwd,
+                    env: task.env,
+                    description: task.description,
+                    depends_on: task.depends_on,
+                })
+                .collect(),
+            pipelines: self.pipeline,
+            watch: self.watch,
+            project_type,
+        }
+    }
+}
+
+/// Keys accepted in each table, `None` means the table is free-form
+///
+/// `[environment]` is written by `doctor` and only listed here.
+fn known_keys(table: &str) -> Option<&'static [&'static str]> {
+    // Task names are chosen by the user, but their fields are not
+    if let Some(task) = table.strip_prefix("tasks.")
+        && !task.contains('.')
+    {
+        return Some(&["cmd", "args", "cwd", "env", "description", "depends_on"]);
+    }
+
+    match table {
+        "" => Some(&["project", "environment", "tasks", "pipeline", "watch"]),
+        "project" => Some(&["type", "name", "version", "build", "run", "commands"]),
+        "project.build" | "project.run" => Some(&["dev", "release"]),
+        "project.commands" => Some(&["fmt", "fmt_check", "lint", "lint_fix", "dependency"]),
+        "pipeline" => Some(&["dev", "build", "run", "test"]),
+        "watch" => Some(&["sources", "ignore"]),
+        "pipeline.dev" | "pipeline.build" | "pipeline.run" | "pipeline.test" => Some(&["stages"]),
+        "pipeline.dev.stages"
+        | "pipeline.build.stages"
+        | "pipeline.run.stages"
+        | "pipeline.test.stages" => Some(&["name", "tasks", "continue_on_error"]),
+        _ => None,
+    }
+}
+
+/// Find the keys lox doesn't know, with their line and column, in the order they are written
+fn find_unknown_keys(content: &str) -> Vec<(String, usize, usize)> {
+    let Ok(document) = DeTable::parse(content) else {
+        return Vec::new();
+    };
+    let mut unknown = unknown_keys(document.get_ref(), "");
+    unknown.sort_by_key(|(_, offset)| *offset);
+    unknown
+        .into_iter()
+        .map(|(key, offset)| {
+            let (line, column) = line_column(content, offset);
+            (key, line, column)
+        })
+        .collect()
+}
+
+/// Collect the dotted path and byte offset of every key not in the schema
+fn unknown_keys(table: &DeTable<'_>, path: &str) -> Vec<(String, usize)> {
+    let known = known_keys(path);
+
+    let mut found = Vec::new();
+    for (key, value) in table {
+        let name = key.get_ref().as_ref();
+        let key_path = if path.is_empty() {
+            name.to_string()
+        } else {
+            format!("{}.{}", path, name)
+        };
+
+        if known.is_some_and(|known| !known.contains(&name)) {
+            found.push((key_path, key.span().start));
+        } else {
+            match value.get_ref() {
+                DeValue::Table(inner) => found.extend(unknown_keys(inner, &key_path)),
+                // Arrays of tables share the schema of their key
+                DeValue::Array(items) => {
+                    for item in items {
+                        if let DeValue::Table(inner) = item.get_ref() {
+                            found.extend(unknown_keys(inner, &key_path));
+                        }
+                    }
+                }
+                _ => {}
+            }
+        }
+    }
+    found
+}
+
+/// Translate a byte offset into a 1-based line and column
+fn line_column(content: &str, offset: usize) -> (usize, usize) {
+    let before = &content[..offset.min(content.len())];
+    let line = before.matches('\n').count() + 1;
+    let column = before.chars().rev().take_while(|c| *c != '\n').count() + 1;
+    (line, column)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const CONFIG: &str = r#"[project]
+type = "custom"
+name = "firmware"
+
+[project.build]
+dev = "west build -b native_sim"
+
+[tasks.flash]
+cmd = "west"
+args = ["flash"]
+depends_on = ["custom_build"]
+
+[[pipeline.build.stages]]
+name = "Build"
+tasks = ["custom_build"]
+"#;
+
+    #[test]
+    fn parse_reads_the_tables() {
+        let project = LoxConfig::parse(CONFIG)
+            .unwrap()
+            .into_project(ProjectCommands::defaults("custom"));
+        assert_eq!(project.project_type, "custom");
+        assert_eq!(project.name, "firmware");
+        assert_eq!(project.version, "unknown");
+        assert_eq!(project.build_commands.dev, "west build -b native_sim");
+        assert_eq!(project.build_commands.release, "unknown");
+
+        let task = &project.user_tasks[0];
+        assert_eq!(task.name, "flash");
+        assert_eq!(task.args, ["flash"]);
+        assert_eq!(task.depends_on, ["custom_build"]);
+
+        let stages = &project.pipelines.build.as_ref().unwrap().stages;
+        assert_eq!(stages[0].tasks, ["custom_build"]);
+    }
+
+    #[test]
+    fn parse_reports_the_line_and_column_of_a_wrong_type() {
+        let content = "[project]\nname = \"app\"\n\n[tasks.lint]\ncmd = 42\n";
+        let Err(ConfigError::Invalid { line, column, .. }) = LoxConfig::parse(content) else {
+            panic!("a number is not a command");
+        };
+        assert_eq!((line, column), (5, 7));
+    }
+
+    #[test]
+    fn parse_reports_the_line_and_column_of_a_syntax_error() {
+        let content = "[project]\nname = \"app\n";
+        let Err(ConfigError::Invalid { line, .. }) = LoxConfig::parse(content) else {
+            panic!("the string is not closed");
+        };
+        assert_eq!(line, 2);
+    }
+
+    #[test]
+    fn unknown_keys_are_found_where_they_are_written() {
+        let content = r#"[project]
+nmae = "app"
+
+[project.run]
+dev = "./app"
+profile = "fast"
+
+[tasks.lint]
+cmd = "ruff"
+cwdir = "src"
+
+[environment]
+anything = "goes"
+
+[[pipeline.dev.stages]]
+name = "Lint"
+tasks = ["lint"]
+retry = true
+"#;
+        assert!(LoxConfig::parse(content).is_ok());
+        assert_eq!(
+            find_unknown_keys(content),
+            [
+                (String::from("project.nmae"), 2, 1),
+                (String::from("project.run.profile"), 6, 1),
+                (String::from("tasks.lint.cwdir"), 10, 1),
+                (String::from("pipeline.dev.stages.retry"), 18, 1),
+            ]
+        );
+    }
+
+    #[test]
+    fn line_column_counts_characters_from_one() {
+        let content = "a = 1\nné = 2\n";
+        assert_eq!(line_column(content, 0), (1, 1));
+        assert_eq!(line_column(content, content.find('=').unwrap()), (1, 3));
+        assert_eq!(line_column(content, content.rfind('=').unwrap()), (2, 4));
+    }
+}
@@ -0,0 +1,43 @@
+use super::{Project, UserTask};
+use crate::tasks;
+
+/// The `[tasks]` table `lox test` runs for a custom project
+pub const TEST_TASK: &str = "test";
+/// The `[tasks]` table `lox clean` runs for a custom project
+pub const CLEAN_TASK: &str = "clean";
+
+/// The tasks running the `[project.build]` commands of a custom project, for the ones that are set
+pub fn build_tasks(project: &Project) -> Vec<UserTask> {
+    [
+        (tasks::CUSTOM_BUILD, &project.build_commands.dev),
+        (tasks::CUSTOM_BUILD_RELEASE, &project.build_commands.release),
+    ]
+    .into_iter()
+    .filter(|(_, command)| *command != "unknown")
+    .filter_map(|(id, command)| {
+        // Commands are split like a shell would, but run without one
+        let Some((cmd, args)) = shlex::split(command)
+            .as_deref()
+            .and_then(|words| words.split_first())
+            .map(|(cmd, args)| (cmd.clone(), args.to_vec()))
+        else {
+            eprintln!("Warning: Invalid command in lox.toml: {}", command);
+            return None;
+        };
+        Some(UserTask {
+            name: id.to_string(),
+            cmd,
+            args,
+            cwd: None,
+            env: Default::default(),
+            description: None,
+            depends_on: Vec::new(),
+        })
+    })
+    .collect()
+}
+
+/// Find a task of the `[tasks]` table, like the `test` task `lox test` runs
+pub fn find_task<'a>(project: &'a Project, name: &str) -> Option<&'a UserTask> {
+    project.user_tasks.iter().find(|task| task.name == name)
+}
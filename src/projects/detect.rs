@@ -1,117 +1,93 @@
 use crate::projects::cargo::detect_cargo_project;
+use crate::projects::cmake::detect_cmake_project;
+use crate::projects::conda::detect_conda_project;
+use crate::projects::config::{CONFIG_FILE, ConfigError, LoxConfig};
+use crate::projects::custom;
 use crate::projects::flang::detect_fortran_project;
 use crate::projects::fpm::detect_fpm_project;
+use crate::projects::go::detect_go_project;
+use crate::projects::make::{self, detect_make_project};
+use crate::projects::node::detect_node_project;
+use crate::projects::pdm::detect_pdm_project;
+use crate::projects::pip::detect_pip_project;
+use crate::projects::poetry::detect_poetry_project;
+use crate::projects::python::read_pyproject;
 use crate::projects::uv::detect_uv_project;
-use crate::projects::{BuildCommands, Project, RunCommands, write_project_to_toml};
+use crate::projects::zig::detect_zig_project;
+use crate::projects::{
+    BuildCommands, Pipelines, Project, ProjectCommands, RunCommands, WatchConfig, is_custom_type,
+    is_node_type, is_runner_type, write_project_to_toml, write_run_commands_to_toml,
+};
+use crate::tasks::{register_node_tasks, register_user_tasks};
+use std::process::exit;
 use tokio::fs::{metadata, read_to_string};
 
 pub async fn get_or_create_project() -> Project {
+    let mut project = load_or_create_project().await;
+
+    // Makefile and justfile projects run their targets as tasks, a `[tasks]` table of the same
+    // name in lox.toml replaces one
+    if is_runner_type(&project.project_type) {
+        let targets = make::read_targets(&project.project_type).await;
+        let mut user_tasks = make::target_tasks(&project.project_type, &targets);
+        user_tasks.append(&mut project.user_tasks);
+        project.user_tasks = user_tasks;
+    } else if is_custom_type(&project.project_type) {
+        // Custom projects build with their `[project.build]` commands, run as tasks
+        project.user_tasks.extend(custom::build_tasks(&project));
+    }
+
+    // Make the tasks from lox.toml available to every command
+    if is_node_type(&project.project_type) {
+        register_node_tasks().await;
+    }
+    register_user_tasks(&project.user_tasks);
+    project
+}
+
+async fn load_or_create_project() -> Project {
     // Check if lox.toml exists
-    let lox_toml_exists = metadata("lox.toml").await.is_ok();
+    let lox_toml_exists = metadata(CONFIG_FILE).await.is_ok();
 
     if lox_toml_exists {
-        // Read and parse lox.toml
-        if let Ok(mut project) = read_project_from_toml().await {
-            // If run commands are unknown, detect them dynamically
-            if project.run_commands.dev == "unknown" || project.run_commands.release == "unknown" {
-                let detected_project = detect_project_info().await;
-                project.run_commands = detected_project.run_commands;
-                write_project_to_toml(&project);
+        // Read and validate lox.toml, a broken file is reported instead of being overwritten
+        let mut project = match read_project_from_toml().await {
+            Ok(project) => project,
+            Err(e) => {
+                eprintln!("[ERROR] + {}", e);
+                exit(1);
+            }
+        };
+
+        // If run commands are unknown, detect them dynamically, custom projects only run their own
+        let is_unknown =
+            project.run_commands.dev == "unknown" || project.run_commands.release == "unknown";
+        if is_unknown && !is_custom_type(&project.project_type) {
+            // Only the commands that were unknown are replaced and saved
+            let detected = detect_project_info().await.run_commands;
+            if project.run_commands.dev == "unknown" {
+                project.run_commands.dev = detected.dev;
+            }
+            if project.run_commands.release == "unknown" {
+                project.run_commands.release = detected.release;
             }
-            return project;
+            write_run_commands_to_toml(&project.run_commands);
         }
+        return project;
     }
 
-    // If lox.toml doesn't exist or can't be parsed, create it
+    // If lox.toml doesn't exist, create it
     let project = detect_project_info().await;
     write_project_to_toml(&project);
     project
 }
 
-pub async fn read_project_from_toml() -> Result<Project, String> {
-    let toml_content = read_to_string("lox.toml")
-        .await
-        .map_err(|e| e.to_string())?;
-
-    let mut project_type = String::from("unknown");
-    let mut name = String::from("unknown");
-    let mut version = String::from("unknown");
-    let mut is_library = false;
-    let mut build_dev = String::from("unknown");
-    let mut build_release = String::from("unknown");
-    let mut run_dev = String::from("unknown");
-    let mut run_release = String::from("unknown");
-    let mut is_rust_project = false;
-    let mut is_uv_project = false;
-    let mut is_fortran_project = false;
-
-    let mut current_section = String::new();
-
-    for line in toml_content.lines() {
-        let trimmed_line = line.trim();
-
-        // Skip empty lines and comments
-        if trimmed_line.is_empty() || trimmed_line.starts_with('#') {
-            continue;
-        }
+pub async fn read_project_from_toml() -> Result<Project, ConfigError> {
+    let toml_content = read_to_string(CONFIG_FILE).await.map_err(ConfigError::Io)?;
 
-        // Handle section headers
-        if trimmed_line.starts_with('[') && trimmed_line.ends_with(']') {
-            current_section = trimmed_line[1..trimmed_line.len() - 1].trim().to_string();
-            continue;
-        }
-
-        // Parse key-value pairs
-        if let Some((key, value)) = trimmed_line.split_once('=') {
-            let key = key.trim();
-            let value = value.trim().trim_matches('"');
-
-            match current_section.as_str() {
-                "project" => match key {
-                    "type" => {
-                        project_type = value.to_string();
-                        is_library = project_type.contains("library");
-                        is_rust_project =
-                            project_type.contains("app") || project_type.contains("library");
-                        is_uv_project = project_type == "uv";
-                        is_fortran_project = project_type == "llvm-f" || project_type == "fpm";
-                    }
-                    "name" => name = value.to_string(),
-                    "version" => version = value.to_string(),
-                    _ => {}
-                },
-                "project.build" => match key {
-                    "dev" => build_dev = value.to_string(),
-                    "release" => build_release = value.to_string(),
-                    _ => {}
-                },
-                "project.run" => match key {
-                    "dev" => run_dev = value.to_string(),
-                    "release" => run_release = value.to_string(),
-                    _ => {}
-                },
-                _ => {}
-            }
-        }
-    }
-
-    Ok(Project {
-        project_type,
-        name,
-        version,
-        is_library,
-        build_commands: BuildCommands {
-            dev: build_dev,
-            release: build_release,
-        },
-        run_commands: RunCommands {
-            dev: run_dev,
-            release: run_release,
-        },
-        is_rust_project,
-        is_uv_project,
-        is_fortran_project,
-    })
+    let config = LoxConfig::parse(&toml_content)?;
+    let defaults = ProjectCommands::detect(config.project_type()).await;
+    Ok(config.into_project(defaults))
 }
 
 pub async fn detect_project_info() -> Project {
@@ -120,8 +96,29 @@ pub async fn detect_project_info() -> Project {
         return cargo_project;
     }
 
+    // The Python detectors share one read of pyproject.toml
+    let pyproject = read_pyproject().await;
+
+    // Try to detect conda and pixi environments next, they wrap Python and Fortran projects
+    if let Some(conda_project) = detect_conda_project(pyproject.as_ref()).await {
+        return conda_project;
+    }
+
+    // Try to detect Poetry and PDM projects next, their pyproject.toml names the tool
+    if let Some(poetry_project) = detect_poetry_project(pyproject.as_ref()).await {
+        return poetry_project;
+    }
+    if let Some(pdm_project) = detect_pdm_project(pyproject.as_ref()).await {
+        return pdm_project;
+    }
+
+    // Try to detect pip project next, before uv which claims every other pyproject.toml
+    if let Some(pip_project) = detect_pip_project(pyproject.as_ref()).await {
+        return pip_project;
+    }
+
     // Try to detect UV project next
-    if let Some(uv_project) = detect_uv_project().await {
+    if let Some(uv_project) = detect_uv_project(pyproject.as_ref()).await {
         return uv_project;
     }
 
@@ -130,17 +127,41 @@ pub async fn detect_project_info() -> Project {
         return fpm_project;
     }
 
+    // Try to detect Go module
+    if let Some(go_project) = detect_go_project().await {
+        return go_project;
+    }
+
+    // Try to detect Zig project
+    if let Some(zig_project) = detect_zig_project().await {
+        return zig_project;
+    }
+
+    // Try to detect CMake project, before the loose Fortran files it may build
+    if let Some(cmake_project) = detect_cmake_project().await {
+        return cmake_project;
+    }
+
+    // Try to detect Node.js package, after the projects that keep a package.json for tooling
+    if let Some(node_project) = detect_node_project().await {
+        return node_project;
+    }
+
     // Try to detect Fortran project
     if let Some(fortran_project) = detect_fortran_project().await {
         return fortran_project;
     }
 
+    // Fall back to the targets of a Makefile or justfile
+    if let Some(make_project) = detect_make_project().await {
+        return make_project;
+    }
+
     // Default to unknown project type
     Project {
         project_type: String::from("unknown"),
         name: String::from("unknown"),
         version: String::from("unknown"),
-        is_library: false,
         build_commands: BuildCommands {
             dev: String::from("unknown"),
             release: String::from("unknown"),
@@ -149,8 +170,12 @@ pub async fn detect_project_info() -> Project {
             dev: String::from("unknown"),
             release: String::from("unknown"),
         },
+        commands: ProjectCommands::defaults("unknown"),
         is_rust_project: false,
         is_uv_project: false,
         is_fortran_project: false,
+        user_tasks: Vec::new(),
+        pipelines: Pipelines::default(),
+        watch: WatchConfig::default(),
     }
 }
@@ -1,4 +1,6 @@
-use crate::projects::{BuildCommands, Project, RunCommands};
+use crate::projects::{
+    BuildCommands, Pipelines, Project, ProjectCommands, RunCommands, WatchConfig,
+};
 use std::path::Path;
 use tokio::fs::{read_dir, read_to_string};
 
@@ -8,17 +10,15 @@ pub async fn detect_fortran_project() -> Option<Project> {
     let has_fortran_files = {
         let mut found = false;
         if let Ok(mut entries) = read_dir(".").await {
-            while let Some(entry_result) = entries.next_entry().await.ok() {
-                if let Some(entry) = entry_result {
-                    let path = entry.path();
-                    if path.is_file() {
-                        if let Some(extension) = path.extension() {
-                            let ext = extension.to_str().unwrap_or("");
-                            if ext == "f90" || ext == "f" || ext == "F90" || ext == "F" {
-                                found = true;
-                                break;
-                            }
-                        }
+            while let Ok(Some(entry)) = entries.next_entry().await {
+                let path = entry.path();
+                if path.is_file()
+                    && let Some(extension) = path.extension()
+                {
+                    let ext = extension.to_str().unwrap_or("");
+                    if ext == "f90" || ext == "f" || ext == "F90" || ext == "F" {
+                        found = true;
+                        break;
                     }
                 }
             }
@@ -39,7 +39,6 @@ pub async fn detect_fortran_project() -> Option<Project> {
         project_type: "llvm-f".to_string(),
         name: main_file_name.clone(),
         version: "0.1.0".to_string(),
-        is_library: false,
         build_commands: BuildCommands {
             dev: format!(
                 "mkdir -p target/dev && flang -g -o target/dev/{0}.out {0}.f90",
@@ -54,33 +53,35 @@ pub async fn detect_fortran_project() -> Option<Project> {
             dev: format!("./target/dev/{}.out", main_file_name),
             release: format!("./target/release/{}.out", main_file_name),
         },
+        commands: ProjectCommands::defaults("llvm-f"),
         is_rust_project: false,
         is_uv_project: false,
         is_fortran_project: true,
+        user_tasks: Vec::new(),
+        pipelines: Pipelines::default(),
+        watch: WatchConfig::default(),
     })
 }
 
 /// Find the main program file by looking for the "program" keyword at the beginning of a line
 pub async fn find_main_program_file() -> Option<std::path::PathBuf> {
     if let Ok(mut entries) = read_dir(".").await {
-        while let Some(entry_result) = entries.next_entry().await.ok() {
-            if let Some(entry) = entry_result {
-                let path = entry.path();
-                if path.is_file() {
-                    if let Some(extension) = path.extension() {
-                        let ext = extension.to_str().unwrap_or("");
-                        if ext == "f90" || ext == "f" || ext == "F90" || ext == "F" {
-                            if let Ok(content) = read_to_string(&path).await {
-                                // Check each line for program declaration at the beginning
-                                for line in content.lines() {
-                                    // Trim whitespace and convert to lowercase
-                                    let trimmed = line.trim().to_lowercase();
-                                    // Check if line starts with "program " (actual program declaration)
-                                    if trimmed.starts_with("program ") {
-                                        return Some(path);
-                                    }
-                                }
-                            }
+        while let Ok(Some(entry)) = entries.next_entry().await {
+            let path = entry.path();
+            if path.is_file()
+                && let Some(extension) = path.extension()
+            {
+                let ext = extension.to_str().unwrap_or("");
+                if (ext == "f90" || ext == "f" || ext == "F90" || ext == "F")
+                    && let Ok(content) = read_to_string(&path).await
+                {
+                    // Check each line for program declaration at the beginning
+                    for line in content.lines() {
+                        // Trim whitespace and convert to lowercase
+                        let trimmed = line.trim().to_lowercase();
+                        // Check if line starts with "program " (actual program declaration)
+                        if trimmed.starts_with("program ") {
+                            return Some(path);
                         }
                     }
                 }
@@ -90,24 +91,44 @@ pub async fn find_main_program_file() -> Option<std::path::PathBuf> {
     None
 }
 
+/// Find the test programs in the `test` directory, sorted by name
+pub async fn find_test_program_files() -> Vec<std::path::PathBuf> {
+    let mut files = Vec::new();
+
+    if let Ok(mut entries) = read_dir("test").await {
+        while let Ok(Some(entry)) = entries.next_entry().await {
+            let path = entry.path();
+            if path.is_file()
+                && let Some(extension) = path.extension()
+            {
+                let ext = extension.to_str().unwrap_or("");
+                if ext == "f90" || ext == "f" || ext == "F90" || ext == "F" {
+                    files.push(path);
+                }
+            }
+        }
+    }
+
+    files.sort();
+    files
+}
+
 /// Detect dependencies between Fortran files
 pub async fn detect_fortran_dependencies() -> Vec<(String, Vec<String>)> {
     let mut dependencies = Vec::new();
 
     // Iterate over all Fortran files in the directory
     if let Ok(mut entries) = read_dir(".").await {
-        while let Some(entry_result) = entries.next_entry().await.ok() {
-            if let Some(entry) = entry_result {
-                let path = entry.path();
-                if path.is_file() {
-                    if let Some(extension) = path.extension() {
-                        let ext = extension.to_str().unwrap_or("");
-                        if ext == "f90" || ext == "f" || ext == "F90" || ext == "F" {
-                            let file_name = path.file_name().unwrap().to_str().unwrap().to_string();
-                            let dep_files = find_file_dependencies(&path).await;
-                            dependencies.push((file_name, dep_files));
-                        }
-                    }
+        while let Ok(Some(entry)) = entries.next_entry().await {
+            let path = entry.path();
+            if path.is_file()
+                && let Some(extension) = path.extension()
+            {
+                let ext = extension.to_str().unwrap_or("");
+                if ext == "f90" || ext == "f" || ext == "F90" || ext == "F" {
+                    let file_name = path.file_name().unwrap().to_str().unwrap().to_string();
+                    let dep_files = find_file_dependencies(&path).await;
+                    dependencies.push((file_name, dep_files));
                 }
             }
         }
@@ -143,9 +164,9 @@ async fn find_file_dependencies(file_path: &Path) -> Vec<String> {
             if line_lower.starts_with("include ") {
                 let mut include_path = line_lower.trim_start_matches("include ").trim();
                 // Remove quotes if present
-                if include_path.starts_with('"') && include_path.ends_with('"') {
-                    include_path = &include_path[1..include_path.len() - 1];
-                } else if include_path.starts_with('\'') && include_path.ends_with('\'') {
+                if (include_path.starts_with('"') && include_path.ends_with('"'))
+                    || (include_path.starts_with('\'') && include_path.ends_with('\''))
+                {
                     include_path = &include_path[1..include_path.len() - 1];
                 }
                 dependencies.push(include_path.to_string());
@@ -159,25 +180,19 @@ async fn find_file_dependencies(file_path: &Path) -> Vec<String> {
 /// Find the file that contains a specific module
 async fn find_module_file(module_name: &str) -> Option<String> {
     if let Ok(mut entries) = read_dir(".").await {
-        while let Some(entry_result) = entries.next_entry().await.ok() {
-            if let Some(entry) = entry_result {
-                let path = entry.path();
-                if path.is_file() {
-                    if let Some(extension) = path.extension() {
-                        let ext = extension.to_str().unwrap_or("");
-                        if ext == "f90" || ext == "f" || ext == "F90" || ext == "F" {
-                            if let Ok(content) = read_to_string(&path).await {
-                                if content
-                                    .to_lowercase()
-                                    .contains(&format!("module {}", module_name))
-                                {
-                                    return Some(
-                                        path.file_name().unwrap().to_str().unwrap().to_string(),
-                                    );
-                                }
-                            }
-                        }
-                    }
+        while let Ok(Some(entry)) = entries.next_entry().await {
+            let path = entry.path();
+            if path.is_file()
+                && let Some(extension) = path.extension()
+            {
+                let ext = extension.to_str().unwrap_or("");
+                if (ext == "f90" || ext == "f" || ext == "F90" || ext == "F")
+                    && let Ok(content) = read_to_string(&path).await
+                    && content
+                        .to_lowercase()
+                        .contains(&format!("module {}", module_name))
+                {
+                    return Some(path.file_name().unwrap().to_str().unwrap().to_string());
                 }
             }
         }
@@ -1,10 +1,12 @@
-use crate::projects::{BuildCommands, Project, RunCommands};
+use crate::projects::{
+    BuildCommands, Pipelines, Project, ProjectCommands, RunCommands, WatchConfig,
+};
 use tokio::fs::{metadata, read_to_string};
 
 /// Detect FPM project information
 pub async fn detect_fpm_project() -> Option<Project> {
     // Check if fpm.toml exists
-    if !metadata("fpm.toml").await.is_ok() {
+    if metadata("fpm.toml").await.is_err() {
         return None;
     }
 
@@ -28,7 +30,6 @@ pub async fn detect_fpm_project() -> Option<Project> {
         project_type: "fpm".to_string(),
         name: project_name,
         version: "0.1.0".to_string(),
-        is_library: false,
         build_commands: BuildCommands {
             dev: "fpm build".to_string(),
             release: "fpm build --profile release".to_string(),
@@ -37,8 +38,12 @@ pub async fn detect_fpm_project() -> Option<Project> {
             dev: "fpm run".to_string(),
             release: "fpm run --profile release".to_string(),
         },
+        commands: ProjectCommands::defaults("fpm"),
         is_rust_project: false,
         is_uv_project: false,
         is_fortran_project: true,
+        user_tasks: Vec::new(),
+        pipelines: Pipelines::default(),
+        watch: WatchConfig::default(),
     })
 }
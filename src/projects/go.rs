@@ -0,0 +1,233 @@
+use super::{BuildCommands, DEV_DIR, Executable, Project, RELEASE_DIR, RunCommands};
+use std::env::consts::EXE_SUFFIX;
+use std::path::{Path, PathBuf};
+use tokio::fs::{read_dir, read_to_string};
+
+/// Directories that never hold a package of the module
+const SKIPPED_DIRS: [&str; 3] = ["vendor", "testdata", "target"];
+
+/// The parts of a go.mod lox reads
+#[derive(Debug, Default)]
+pub struct GoMod {
+    /// The module path, like `example.com/app`
+    pub module: Option<String>,
+    /// The Go version of the `go` directive
+    pub go: Option<String>,
+}
+
+/// Detect a Go module
+pub async fn detect_go_project() -> Option<Project> {
+    let go_mod = read_go_mod().await?;
+    let name = go_mod
+        .module
+        .as_deref()
+        .map(binary_name)
+        .unwrap_or_else(|| String::from("unknown"));
+
+    // `lox run` runs the only main package of the module, `--bin` picks among several
+    let binaries = main_packages(&go_mod).await;
+    let (run_dev, run_release) = match binaries.as_slice() {
+        [binary] => (
+            binary_path(DEV_DIR, binary),
+            binary_path(RELEASE_DIR, binary),
+        ),
+        _ => (String::from("unknown"), String::from("unknown")),
+    };
+
+    Some(Project::new(
+        "go",
+        name,
+        String::from("unknown"),
+        BuildCommands {
+            dev: build_command(false),
+            release: build_command(true),
+        },
+        RunCommands {
+            dev: run_dev,
+            release: run_release,
+        },
+    ))
+}
+
+/// Read the module path and Go version of go.mod, `None` without a go.mod
+pub async fn read_go_mod() -> Option<GoMod> {
+    let content = read_to_string("go.mod").await.ok()?;
+    Some(parse_go_mod(&content))
+}
+
+/// Read the `module` and `go` directives of go.mod, the other directives are skipped
+fn parse_go_mod(content: &str) -> GoMod {
+    let mut go_mod = GoMod::default();
+    for line in content.lines() {
+        let line = line.split("//").next().unwrap_or_default().trim();
+        let Some((directive, value)) = line.split_once(char::is_whitespace) else {
+            continue;
+        };
+        let value = value.trim();
+        match directive {
+            "module" => go_mod.module = Some(value.trim_matches('"').to_string()),
+            "go" => go_mod.go = Some(value.to_string()),
+            _ => {}
+        }
+    }
+    go_mod
+}
+
+/// The arguments of `go` that build every main package of the module into a profile directory
+///
+/// The release build strips the paths and the debug information.
+pub fn build_args(release: bool) -> Vec<String> {
+    let mut args = vec![String::from("build")];
+    if release {
+        args.push(String::from("-trimpath"));
+        args.push(String::from("-ldflags=-s -w"));
+    }
+    let dir = if release { RELEASE_DIR } else { DEV_DIR };
+    args.extend([
+        String::from("-o"),
+        format!("{}/", dir),
+        String::from("./..."),
+    ]);
+    args
+}
+
+/// The `go build` command line of a profile, quoted like a shell would
+pub fn build_command(release: bool) -> String {
+    let args = build_args(release);
+    let words = std::iter::once("go").chain(args.iter().map(String::as_str));
+    shlex::try_join(words).unwrap_or_else(|_| format!("go {}", args.join(" ")))
+}
+
+/// Find the executables the build puts in a profile directory, sorted by name
+pub async fn binaries(release: bool) -> Vec<Executable> {
+    let go_mod = read_go_mod().await.unwrap_or_default();
+    let dir = if release { RELEASE_DIR } else { DEV_DIR };
+    main_packages(&go_mod)
+        .await
+        .into_iter()
+        .map(|name| Executable {
+            path: binary_path(dir, &name),
+            name,
+        })
+        .collect()
+}
+
+fn binary_path(dir: &str, name: &str) -> String {
+    format!("./{}/{}{}", dir, name, EXE_SUFFIX)
+}
+
+/// The executable name `go build` gives a package, the last element of its import path
+///
+/// A major version suffix like `/v2` is skipped.
+fn binary_name(import_path: &str) -> String {
+    let mut elements = import_path.rsplit('/');
+    let last = elements.next().unwrap_or(import_path);
+    let is_major_version =
+        last.len() > 1 && last.starts_with('v') && last[1..].chars().all(|c| c.is_ascii_digit());
+    match elements.next() {
+        Some(previous) if is_major_version => previous.to_string(),
+        _ => last.to_string(),
+    }
+}
+
+/// Find the names of the main packages of the module, sorted
+async fn main_packages(go_mod: &GoMod) -> Vec<String> {
+    let mut names = Vec::new();
+    let mut dirs = vec![PathBuf::from(".")];
+    while let Some(dir) = dirs.pop() {
+        let Ok(mut entries) = read_dir(&dir).await else {
+            continue;
+        };
+        let mut is_main = false;
+        while let Ok(Some(entry)) = entries.next_entry().await {
+            let path = entry.path();
+            let file_name = entry.file_name().to_string_lossy().to_string();
+            if path.is_dir() {
+                // Hidden and `_` directories are ignored by the go tool too
+                let ignored = file_name.starts_with('.')
+                    || file_name.starts_with('_')
+                    || SKIPPED_DIRS.contains(&file_name.as_str());
+                if !ignored {
+                    dirs.push(path);
+                }
+            } else if !is_main
+                && file_name.ends_with(".go")
+                && !file_name.ends_with("_test.go")
+                && is_main_file(&path).await
+            {
+                is_main = true;
+            }
+        }
+
+        if is_main {
+            let name = if dir == Path::new(".") {
+                go_mod.module.as_deref().map(binary_name)
+            } else {
+                dir.file_name()
+                    .map(|name| name.to_string_lossy().to_string())
+            };
+            names.extend(name);
+        }
+    }
+    names.sort();
+    names
+}
+
+/// Whether a Go file belongs to the `main` package
+async fn is_main_file(path: &Path) -> bool {
+    let Ok(content) = read_to_string(path).await else {
+        return false;
+    };
+    content
+        .lines()
+        .map(str::trim)
+        .find(|line| line.starts_with("package "))
+        .is_some_and(|line| line.split_whitespace().nth(1) == Some("main"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn go_mod_directives_are_read() {
+        let content = "\
+// Module of the demo server
+module github.com/acme/server/v2 // moved in 2024
+
+go 1.22
+toolchain go1.22.4
+
+require (
+\tgithub.com/spf13/cobra v1.8.0
+\tgolang.org/x/sync v0.7.0 // indirect
+)
+
+replace github.com/acme/lib => ../lib
+";
+        let go_mod = parse_go_mod(content);
+        assert_eq!(go_mod.module.as_deref(), Some("github.com/acme/server/v2"));
+        assert_eq!(go_mod.go.as_deref(), Some("1.22"));
+    }
+
+    #[test]
+    fn go_mod_accepts_tabs_and_quoted_paths() {
+        let go_mod = parse_go_mod("module\t\"example.com/app\"\ngo\t1.21.0\n");
+        assert_eq!(go_mod.module.as_deref(), Some("example.com/app"));
+        assert_eq!(go_mod.go.as_deref(), Some("1.21.0"));
+    }
+
+    #[test]
+    fn go_mod_without_directives_is_empty() {
+        let go_mod = parse_go_mod("require example.com/lib v1.0.0\n");
+        assert_eq!(go_mod.module, None);
+        assert_eq!(go_mod.go, None);
+    }
+
+    #[test]
+    fn binary_name_skips_the_major_version() {
+        assert_eq!(binary_name("github.com/acme/server/v2"), "server");
+        assert_eq!(binary_name("example.com/app"), "app");
+        assert_eq!(binary_name("tool"), "tool");
+    }
+}
@@ -1,16 +1,57 @@
-use std::fs::write;
+use crate::projects::config::CONFIG_FILE;
+use crate::projects::node;
+use serde::Deserialize;
+use std::collections::BTreeMap;
+use std::fs::{read_to_string, write};
+use std::path::{MAIN_SEPARATOR, Path};
+use toml_edit::{DocumentMut, Item, Table, TableLike, TomlError, Value, value};
 
 #[derive(Debug)]
 pub struct Project {
     pub project_type: String,
     pub name: String,
     pub version: String,
-    pub is_library: bool,
     pub build_commands: BuildCommands,
     pub run_commands: RunCommands,
+    pub commands: ProjectCommands,
     pub is_rust_project: bool,
     pub is_uv_project: bool,
     pub is_fortran_project: bool,
+    pub user_tasks: Vec<UserTask>,
+    pub pipelines: Pipelines,
+    pub watch: WatchConfig,
+}
+
+impl Project {
+    /// A detected project with the default commands of its type, before lox.toml is read
+    pub fn new(
+        project_type: &str,
+        name: String,
+        version: String,
+        build_commands: BuildCommands,
+        run_commands: RunCommands,
+    ) -> Self {
+        Project {
+            project_type: project_type.to_string(),
+            name,
+            version,
+            build_commands,
+            run_commands,
+            commands: ProjectCommands::defaults(project_type),
+            is_rust_project: false,
+            is_uv_project: false,
+            is_fortran_project: false,
+            user_tasks: Vec::new(),
+            pipelines: Pipelines::default(),
+            watch: WatchConfig::default(),
+        }
+    }
+}
+
+/// The name of the current directory, for projects whose manifest doesn't name them
+pub fn directory_name() -> Option<String> {
+    let dir = std::env::current_dir().ok()?;
+    Some(dir.file_name()?.to_string_lossy().to_string())
 }
 
 #[derive(Debug)]
@@ -25,29 +66,315 @@ pub struct RunCommands {
     pub release: String,
 }
 
+/// Whether a project type is built with cargo
+pub fn is_rust_type(project_type: &str) -> bool {
+    project_type.contains("app") || project_type.contains("library") || project_type == "workspace"
+}
+
+/// Whether a project type is a Python project with its own environment
+pub fn is_python_type(project_type: &str) -> bool {
+    matches!(project_type, "uv" | "pip" | "poetry" | "pdm")
+}
+
+/// Whether a project type runs inside a conda or pixi environment
+pub fn is_environment_type(project_type: &str) -> bool {
+    project_type == "conda" || project_type == "pixi"
+}
+
+/// Whether a project type is a Node.js package, named after its package manager
+pub fn is_node_type(project_type: &str) -> bool {
+    matches!(project_type, "npm" | "pnpm" | "yarn")
+}
+
+/// Whether a project type runs the targets of a Makefile or the recipes of a justfile
+pub fn is_runner_type(project_type: &str) -> bool {
+    project_type == "make" || project_type == "just"
+}
+
+/// Whether a project type runs only the commands of lox.toml, without any detection
+pub fn is_custom_type(project_type: &str) -> bool {
+    project_type == "custom"
+}
+
+/// Whether a project type builds executables lox runs from the build directory
+pub fn is_native_type(project_type: &str) -> bool {
+    matches!(project_type, "cmake" | "go" | "zig")
+}
+
+/// The build directory of the dev profile of CMake and Go, next to the native Flang builds
+pub const DEV_DIR: &str = "target/dev";
+/// The build directory of the release profile
+pub const RELEASE_DIR: &str = "target/release";
+
+/// An executable a native build puts in its build directory
+#[derive(Debug, Clone)]
+pub struct Executable {
+    pub name: String,
+    /// Where the build puts the executable, relative to the project
+    pub path: String,
+}
+
+/// Write a path with `/` separators, which Windows accepts and shlex doesn't read as escapes
+pub fn slash_path(path: &Path) -> String {
+    path.to_string_lossy().replace(MAIN_SEPARATOR, "/")
+}
+
+/// Write the path of an executable as a run command, quoted when it has spaces
+pub fn command_path(path: &Path) -> String {
+    let path = slash_path(path);
+    match shlex::try_quote(&path) {
+        Ok(quoted) => quoted.into_owned(),
+        Err(_) => path,
+    }
+}
+
+/// Commands of the `[project.commands]` table
+#[derive(Debug)]
+pub struct ProjectCommands {
+    pub fmt: String,
+    /// Checks the formatting without modifying files
+    pub fmt_check: Option<String>,
+    pub lint: String,
+    /// Lints and applies the suggested fixes
+    pub lint_fix: Option<String>,
+    pub dependency: String,
+}
+
+impl ProjectCommands {
+    /// The commands of a project type, read from package.json for Node.js packages
+    pub async fn detect(project_type: &str) -> Self {
+        // Node.js packages bring their own commands as scripts
+        if is_node_type(project_type) {
+            let package = node::read_package_json().await;
+            return node::default_commands(project_type, &package);
+        }
+        Self::defaults(project_type)
+    }
+
+    /// The built-in commands of a project type, "unknown" when it has none
+    ///
+    /// Node.js packages have none without their package.json, see `ProjectCommands::detect`.
+    pub fn defaults(project_type: &str) -> Self {
+        let (fmt, fmt_check, lint, lint_fix, dependency) = if is_rust_type(project_type) {
+            (
+                "cargo fmt",
+                Some("cargo fmt --check"),
+                "cargo check",
+                Some("cargo fix --allow-dirty"),
+                "cargo update",
+            )
+        } else if project_type == "uv" {
+            (
+                "uvx ruff format",
+                Some("uvx ruff format --check"),
+                "uvx ruff check",
+                Some("uvx ruff check --fix"),
+                "uv update",
+            )
+        } else if project_type == "poetry" || project_type == "pdm" {
+            let dependency = if project_type == "poetry" {
+                "poetry update"
+            } else {
+                "pdm update"
+            };
+            ("unknown", None, "unknown", None, dependency)
+        } else if project_type == "go" {
+            ("gofmt -w .", None, "go vet ./...", None, "go mod tidy")
+        } else if project_type == "zig" {
+            (
+                "zig fmt .",
+                Some("zig fmt --check ."),
+                "unknown",
+                None,
+                "unknown",
+            )
+        } else if project_type == "conda" {
+            ("unknown", None, "unknown", None, "conda env update --prune")
+        } else if project_type == "pixi" {
+            ("unknown", None, "unknown", None, "pixi update")
+        } else if project_type == "pip" {
+            (
+                "unknown",
+                None,
+                "unknown",
+                None,
+                "pip install -r requirements.txt",
+            )
+        } else {
+            ("unknown", None, "unknown", None, "unknown")
+        };
+
+        Self {
+            fmt: fmt.to_string(),
+            fmt_check: fmt_check.map(String::from),
+            lint: lint.to_string(),
+            lint_fix: lint_fix.map(String::from),
+            dependency: dependency.to_string(),
+        }
+    }
+}
+
+/// Pipelines defined in the `[pipeline]` table of lox.toml
+#[derive(Debug, Default, Deserialize)]
+pub struct Pipelines {
+    pub dev: Option<Pipeline>,
+    pub build: Option<Pipeline>,
+    pub run: Option<Pipeline>,
+    pub test: Option<Pipeline>,
+}
+
+/// An ordered list of stages, each one running a list of task IDs
+#[derive(Debug, Clone, Deserialize)]
+pub struct Pipeline {
+    pub stages: Vec<Stage>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct Stage {
+    pub name: String,
+    pub tasks: Vec<String>,
+    #[serde(default)]
+    pub continue_on_error: bool,
+}
+
+/// The `[watch]` table of lox.toml
+#[derive(Debug, Default, Deserialize)]
+pub struct WatchConfig {
+    /// Glob patterns of the sources that trigger a rerun, replacing the ones of the project type
+    #[serde(default)]
+    pub sources: Vec<String>,
+    /// Glob patterns of paths that never trigger a rerun, relative to the project
+    #[serde(default)]
+    pub ignore: Vec<String>,
+}
+
+/// A task defined in the `[tasks]` table of lox.toml
+#[derive(Debug, Clone)]
+pub struct UserTask {
+    pub name: String,
+    pub cmd: String,
+    pub args: Vec<String>,
+    pub cwd: Option<String>,
+    pub env: BTreeMap<String, String>,
+    pub description: Option<String>,
+    pub depends_on: Vec<String>,
+}
+
+/// Save a detected project to lox.toml, leaving out the values detection could not resolve
 pub fn write_project_to_toml(project: &Project) {
-    // Create TOML content for project configuration
-    let mut toml_content = format!(
-        "[project]\ntype = \"{}\"\nname = \"{}\"\nversion = \"{}\"\n",
-        project.project_type, project.name, project.version
-    );
+    // Only the keys lox owns are touched, everything else in lox.toml is kept as written
+    update_lox_toml(|document| set_project_keys(document, project));
+}
+
+/// Save the run commands detected for a lox.toml that had none
+pub fn write_run_commands_to_toml(run_commands: &RunCommands) {
+    update_lox_toml(|document| set_run_keys(document, run_commands));
+}
+
+fn set_project_keys(document: &mut DocumentMut, project: &Project) {
+    set_resolved_string(document, &["project"], "type", &project.project_type);
+    set_resolved_string(document, &["project"], "name", &project.name);
+    set_resolved_string(document, &["project"], "version", &project.version);
 
     // Add build commands
-    toml_content.push_str("\n[project.build]\n");
-    toml_content.push_str(format!("dev = \"{}\"\n", project.build_commands.dev).as_str());
-    toml_content.push_str(format!("release = \"{}\"\n", project.build_commands.release).as_str());
+    let build = &["project", "build"];
+    set_resolved_string(document, build, "dev", &project.build_commands.dev);
+    set_resolved_string(document, build, "release", &project.build_commands.release);
 
     // Add run commands
-    toml_content.push_str("\n[project.run]\n");
-    toml_content.push_str(format!("dev = \"{}\"\n", project.run_commands.dev).as_str());
-    toml_content.push_str(format!("release = \"{}\"\n", project.run_commands.release).as_str());
+    set_run_keys(document, &project.run_commands);
+}
+
+fn set_run_keys(document: &mut DocumentMut, run_commands: &RunCommands) {
+    let run = &["project", "run"];
+    set_resolved_string(document, run, "dev", &run_commands.dev);
+    set_resolved_string(document, run, "release", &run_commands.release);
+}
 
-    // Write to lox.toml
-    if let Err(e) = write("lox.toml", toml_content) {
+// A missing key reads as "unknown" again, so there is nothing to write for it
+fn set_resolved_string(document: &mut DocumentMut, path: &[&str], key: &str, new_value: &str) {
+    if new_value != "unknown" {
+        set_toml_string(document, path, key, new_value);
+    }
+}
+
+/// Edit lox.toml in place, keeping comments, ordering and unknown tables
+///
+/// Returns whether the file is up to date afterwards.
+pub fn update_lox_toml<F>(edit: F) -> bool
+where
+    F: FnOnce(&mut DocumentMut),
+{
+    let original = read_to_string(CONFIG_FILE).unwrap_or_default();
+    let updated = match edit_toml(&original, edit) {
+        Ok(updated) => updated,
+        Err(e) => {
+            eprintln!(
+                "Warning: Failed to write project configuration to lox.toml: {}",
+                e
+            );
+            return false;
+        }
+    };
+
+    // Leave the file alone when nothing changed
+    if updated == original {
+        return true;
+    }
+
+    if let Err(e) = write(CONFIG_FILE, updated) {
         eprintln!(
             "Warning: Failed to write project configuration to lox.toml: {}",
             e
         );
+        return false;
+    }
+    true
+}
+
+/// Apply an edit to the text of a TOML document, everything it doesn't touch stays as written
+fn edit_toml<F>(original: &str, edit: F) -> Result<String, TomlError>
+where
+    F: FnOnce(&mut DocumentMut),
+{
+    let mut document = original.parse::<DocumentMut>()?;
+    edit(&mut document);
+    Ok(document.to_string())
+}
+
+/// Set a string key in a nested table, creating the tables it needs
+///
+/// An existing value keeps its comments and formatting when it is replaced.
+pub fn set_toml_string(document: &mut DocumentMut, path: &[&str], key: &str, new_value: &str) {
+    // Separate new sections from the previous ones, except at the top of the file
+    let is_empty = document.as_table().is_empty();
+
+    let mut table: &mut dyn TableLike = document.as_table_mut();
+    for name in path {
+        let item = table.entry(name).or_insert_with(|| {
+            let mut new_table = Table::new();
+            if !is_empty {
+                new_table.decor_mut().set_prefix("\n");
+            }
+            Item::Table(new_table)
+        });
+        let Some(next) = item.as_table_like_mut() else {
+            return;
+        };
+        table = next;
+    }
+
+    match table.get_mut(key) {
+        Some(Item::Value(existing)) => {
+            if existing.as_str() != Some(new_value) {
+                let decor = existing.decor().clone();
+                *existing = Value::from(new_value);
+                *existing.decor_mut() = decor;
+            }
+        }
+        _ => {
+            table.insert(key, value(new_value));
+        }
     }
 }
 
@@ -59,3 +386,92 @@ pub fn format_os_name(os: &str) -> String {
         _ => os.to_string(),
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const LOX_TOML: &str = r#"# Written by hand, lox keeps it as it is
+
+[project]
+type = "app(bin)" # detected
+name = "demo"
+version = "0.1.0"
+
+[project.build]
+dev = "cargo build"
+release = "cargo build --release"
+
+[project.run]
+dev = "unknown"
+release = "unknown"
+
+# Checked by `lox dev`
+[project.commands]
+fmt = "cargo fmt"
+lint = "cargo clippy"
+
+[environment]
+os = "Linux"
+arch = "x86_64" # the build machine
+
+[deploy]
+host = "example.org"
+ports = [80, 443]
+"#;
+
+    fn demo_project(run: &str) -> Project {
+        Project::new(
+            "app(bin)",
+            String::from("demo"),
+            String::from("0.1.0"),
+            BuildCommands {
+                dev: String::from("cargo build"),
+                release: String::from("cargo build --release"),
+            },
+            RunCommands {
+                dev: run.to_string(),
+                release: run.to_string(),
+            },
+        )
+    }
+
+    #[test]
+    fn unchanged_project_keeps_the_file() {
+        let project = demo_project("unknown");
+        let updated = edit_toml(LOX_TOML, |document| set_project_keys(document, &project));
+        assert_eq!(updated.unwrap(), LOX_TOML);
+    }
+
+    #[test]
+    fn resolved_run_commands_only_change_their_keys() {
+        let run_commands = demo_project("./target/dev/demo").run_commands;
+        let updated = edit_toml(LOX_TOML, |document| set_run_keys(document, &run_commands));
+        let expected = LOX_TOML
+            .replace("dev = \"unknown\"", "dev = \"./target/dev/demo\"")
+            .replace("release = \"unknown\"", "release = \"./target/dev/demo\"");
+        assert_eq!(updated.unwrap(), expected);
+    }
+
+    #[test]
+    fn unknown_values_are_not_written() {
+        let mut project = demo_project("unknown");
+        project.version = String::from("unknown");
+        let updated = edit_toml("", |document| set_project_keys(document, &project)).unwrap();
+        assert!(!updated.contains("unknown"));
+        assert!(!updated.contains("[project.run]"));
+        assert!(updated.contains("name = \"demo\""));
+    }
+
+    #[test]
+    fn replaced_value_keeps_its_comment() {
+        let updated = edit_toml(LOX_TOML, |document| {
+            set_toml_string(document, &["project"], "type", "app(lib)");
+            set_toml_string(document, &["environment"], "arch", "aarch64");
+        });
+        let expected = LOX_TOML
+            .replace("\"app(bin)\" # detected", "\"app(lib)\" # detected")
+            .replace("\"x86_64\" # the build", "\"aarch64\" # the build");
+        assert_eq!(updated.unwrap(), expected);
+    }
+}
@@ -0,0 +1,342 @@
+use super::{BuildCommands, Project, RunCommands, UserTask, directory_name};
+use tokio::fs::{metadata, read_to_string};
+
+/// The files of each task runner, in the order they are looked for
+///
+/// A justfile is written as a list of tasks, so it wins over a Makefile next to it.
+pub const RUNNER_FILES: [(&str, &str); 6] = [
+    ("justfile", "just"),
+    ("Justfile", "just"),
+    (".justfile", "just"),
+    ("GNUmakefile", "make"),
+    ("makefile", "make"),
+    ("Makefile", "make"),
+];
+
+/// The targets `lox dev` and `lox build` run, the first one found
+pub const BUILD_TARGETS: [&str; 2] = ["build", "all"];
+/// The target `lox run` and `lox dash` run
+pub const RUN_TARGET: &str = "run";
+/// The target `lox test` runs
+pub const TEST_TARGET: &str = "test";
+/// The target `lox clean` runs
+pub const CLEAN_TARGET: &str = "clean";
+
+/// A target of a Makefile, or a recipe of a justfile
+#[derive(Debug, Clone)]
+pub struct Target {
+    pub name: String,
+    /// The comment above the target, or after `##` on its line
+    pub description: Option<String>,
+}
+
+/// Detect a project driven by a Makefile or a justfile, when nothing else matched
+pub async fn detect_make_project() -> Option<Project> {
+    let (runner, _) = runner_file().await?;
+    let targets = read_targets(runner).await;
+    let has_target = |name: &str| targets.iter().any(|target| target.name == name);
+
+    let build = BUILD_TARGETS
+        .iter()
+        .find(|name| has_target(name))
+        .map(|name| format!("{} {}", runner, name))
+        .unwrap_or_else(|| String::from("unknown"));
+    let run = if has_target(RUN_TARGET) {
+        format!("{} {}", runner, RUN_TARGET)
+    } else {
+        String::from("unknown")
+    };
+
+    let name = directory_name().unwrap_or_else(|| String::from("unknown"));
+    Some(Project::new(
+        runner,
+        name,
+        String::from("unknown"),
+        BuildCommands {
+            dev: build.clone(),
+            release: build,
+        },
+        RunCommands {
+            dev: run.clone(),
+            release: run,
+        },
+    ))
+}
+
+/// Find the task runner of the project and its file, like `("make", "Makefile")`
+pub async fn runner_file() -> Option<(&'static str, &'static str)> {
+    for (file, runner) in RUNNER_FILES {
+        if metadata(file).await.is_ok() {
+            return Some((runner, file));
+        }
+    }
+    None
+}
+
+/// Read the targets of the Makefile or the recipes of the justfile, in the order they are written
+pub async fn read_targets(runner: &str) -> Vec<Target> {
+    for (file, file_runner) in RUNNER_FILES {
+        if file_runner != runner {
+            continue;
+        }
+        if let Ok(content) = read_to_string(file).await {
+            return if runner == "just" {
+                parse_justfile(&content)
+            } else {
+                parse_makefile(&content)
+            };
+        }
+    }
+    Vec::new()
+}
+
+/// The tasks running each target, named after it, like `make test` for the `test` task
+pub fn target_tasks(runner: &str, targets: &[Target]) -> Vec<UserTask> {
+    targets
+        .iter()
+        .map(|target| UserTask {
+            name: target.name.clone(),
+            cmd: runner.to_string(),
+            args: vec![target.name.clone()],
+            cwd: None,
+            env: Default::default(),
+            description: target.description.clone(),
+            depends_on: Vec::new(),
+        })
+        .collect()
+}
+
+/// The first of the targets the project has a task for, like `build` in [`BUILD_TARGETS`]
+pub fn find_target<'a>(project: &Project, names: &[&'a str]) -> Option<&'a str> {
+    names
+        .iter()
+        .copied()
+        .find(|name| project.user_tasks.iter().any(|task| task.name == *name))
+}
+
+/// The file of a task runner in messages, `Makefile` or `justfile`
+pub fn runner_file_name(runner: &str) -> &'static str {
+    if runner == "just" {
+        "justfile"
+    } else {
+        "Makefile"
+    }
+}
+
+/// Whether a name can be run as a task, not a file, pattern or special target
+fn is_task_name(name: &str) -> bool {
+    !name.is_empty()
+        && !name.starts_with('.')
+        && name
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || matches!(c, '_' | '-'))
+}
+
+/// Read the explicit targets of a Makefile, recipes and variables are skipped
+fn parse_makefile(content: &str) -> Vec<Target> {
+    let mut targets: Vec<Target> = Vec::new();
+    let mut comment: Option<String> = None;
+    let mut in_define = false;
+
+    for line in content.lines() {
+        // Multi-line variables can hold anything, colons included
+        let trimmed = line.trim();
+        if in_define {
+            in_define = trimmed != "endef";
+            continue;
+        }
+        if trimmed.starts_with("define ") || trimmed == "define" {
+            in_define = true;
+            continue;
+        }
+
+        if line.starts_with('\t') || line.starts_with(' ') {
+            continue;
+        }
+        if let Some(text) = trimmed.strip_prefix('#') {
+            comment = Some(text.trim_start_matches('#').trim().to_string());
+            continue;
+        }
+        let Some((names, rest)) = trimmed.split_once(':') else {
+            comment = None;
+            continue;
+        };
+        // `A := 1`, `A ::= 1` and `A = 1:2` are variables, not rules
+        if names.contains('=') || rest.starts_with('=') || rest.starts_with(":=") {
+            comment = None;
+            continue;
+        }
+
+        // `build: deps ## Build the project` is the usual way to document a target
+        let description = rest
+            .split_once("##")
+            .map(|(_, text)| text.trim().to_string())
+            .or_else(|| comment.take())
+            .filter(|text| !text.is_empty());
+        for name in names.split_whitespace() {
+            if is_task_name(name) && !targets.iter().any(|target| target.name == name) {
+                targets.push(Target {
+                    name: name.to_string(),
+                    description: description.clone(),
+                });
+            }
+        }
+        comment = None;
+    }
+    targets
+}
+
+/// Read the public recipes of a justfile, `_` recipes and `[private]` ones are skipped
+fn parse_justfile(content: &str) -> Vec<Target> {
+    let mut targets: Vec<Target> = Vec::new();
+    let mut comment: Option<String> = None;
+    let mut is_private = false;
+
+    for line in content.lines() {
+        if line.starts_with(char::is_whitespace) {
+            continue;
+        }
+        let trimmed = line.trim();
+        if trimmed.is_empty() {
+            comment = None;
+            is_private = false;
+            continue;
+        }
+        if let Some(text) = trimmed.strip_prefix('#') {
+            if !text.starts_with('!') {
+                comment = Some(text.trim().to_string());
+            }
+            continue;
+        }
+        // Attributes, like `[private]`, `[group('ci')]` or `[doc('Build the project')]`
+        if let Some(attribute) = trimmed.strip_prefix('[') {
+            is_private |= attribute.starts_with("private");
+            if let Some(doc) = attribute.strip_prefix("doc(") {
+                comment = Some(
+                    doc.trim_end_matches([']', ')'])
+                        .trim_matches(['"', '\''])
+                        .to_string(),
+                );
+            }
+            continue;
+        }
+
+        let Some((header, _)) = trimmed.split_once(':') else {
+            comment = None;
+            continue;
+        };
+        // Settings, aliases, imports and `name := value` assignments are not recipes
+        let first = header.split_whitespace().next().unwrap_or_default();
+        let is_statement = matches!(first, "set" | "alias" | "export" | "import" | "mod")
+            || trimmed[header.len()..].starts_with(":=");
+        let name = first.trim_start_matches('@');
+        if !is_statement
+            && !is_private
+            && !name.starts_with('_')
+            && is_task_name(name)
+            && !targets.iter().any(|target| target.name == name)
+        {
+            targets.push(Target {
+                name: name.to_string(),
+                description: comment.take().filter(|text| !text.is_empty()),
+            });
+        }
+        comment = None;
+        is_private = false;
+    }
+    targets
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn names(targets: &[Target]) -> Vec<&str> {
+        targets.iter().map(|target| target.name.as_str()).collect()
+    }
+
+    #[test]
+    fn makefile_targets_skip_variables_patterns_and_recipes() {
+        let makefile = "\
+CC := gcc
+CFLAGS = -O2 -Wall
+PREFIX ?= /usr/local
+SRCS = $(wildcard src/*.c)
+OBJS = $(SRCS:.c=.o)
+
+.PHONY: all build test clean install
+.DEFAULT_GOAL := all
+
+define HELP
+usage: make build
+endef
+
+all: build ## Build everything
+
+# Compile the program
+build: $(OBJS)
+\t$(CC) $(CFLAGS) -o app $(OBJS)
+
+%.o: %.c
+\t$(CC) $(CFLAGS) -c $< -o $@
+
+app.o lib.o: config.h
+
+test: build
+\t./app --self-test
+\t@echo done: ok
+
+clean:
+\trm -f app src/*.o
+
+install: build
+\tinstall -m 755 app $(PREFIX)/bin
+build: extra-dependency
+";
+        let targets = parse_makefile(makefile);
+        assert_eq!(
+            names(&targets),
+            ["all", "build", "test", "clean", "install"]
+        );
+        assert_eq!(targets[0].description.as_deref(), Some("Build everything"));
+        assert_eq!(
+            targets[1].description.as_deref(),
+            Some("Compile the program")
+        );
+        assert_eq!(targets[2].description, None);
+    }
+
+    #[test]
+    fn justfile_recipes_skip_settings_and_private_recipes() {
+        let justfile = "\
+set shell := [\"bash\", \"-c\"]
+alias b := build
+version := `git describe`
+export RUST_LOG := \"info\"
+
+# Build the project
+build profile=\"dev\":
+    cargo build --profile {{profile}}
+
+[private]
+helper:
+    echo helper
+
+_setup:
+    echo setup
+
+[group('ci')]
+[doc('Run the tests')]
+test *args: build
+    cargo test {{args}}
+
+@run: build
+    ./target/debug/app
+";
+        let targets = parse_justfile(justfile);
+        assert_eq!(names(&targets), ["build", "test", "run"]);
+        assert_eq!(targets[0].description.as_deref(), Some("Build the project"));
+        assert_eq!(targets[1].description.as_deref(), Some("Run the tests"));
+        assert_eq!(targets[2].description, None);
+    }
+}
@@ -1,13 +1,25 @@
 pub mod cargo;
+pub mod cmake;
+pub mod conda;
+pub mod config;
+pub mod custom;
 pub mod detect;
 pub mod flang;
 pub mod fpm;
+pub mod go;
+pub mod make;
+pub mod node;
+pub mod pdm;
+pub mod pip;
+pub mod poetry;
+pub mod python;
 pub mod uv;
+pub mod zig;
 
 // Re-export shared structs and functions
 pub use self::lib::*;
 
 // Re-export main project management functions
-pub use self::detect::{detect_project_info, get_or_create_project};
+pub use self::detect::get_or_create_project;
 
 mod lib;
@@ -0,0 +1,280 @@
+use super::{BuildCommands, Project, ProjectCommands, RunCommands};
+use serde::Deserialize;
+use std::collections::BTreeMap;
+use tokio::fs::{metadata, read_to_string};
+
+/// The lockfiles of the package managers, in the order they are looked for
+pub const LOCKFILES: [(&str, &str); 3] = [
+    ("pnpm-lock.yaml", "pnpm"),
+    ("yarn.lock", "yarn"),
+    ("package-lock.json", "npm"),
+];
+
+/// The test script `npm init` writes, which always fails
+const PLACEHOLDER_TEST: &str = "echo \"Error: no test specified\" && exit 1";
+
+/// The parts of a package.json lox reads
+#[derive(Debug, Default, Deserialize)]
+pub struct PackageJson {
+    pub name: Option<String>,
+    pub version: Option<String>,
+    pub main: Option<String>,
+    #[serde(default)]
+    pub scripts: BTreeMap<String, String>,
+    /// The package manager pinned for Corepack, like `pnpm@9.1.0`
+    #[serde(rename = "packageManager")]
+    pub package_manager: Option<String>,
+}
+
+impl PackageJson {
+    /// Whether package.json has a script, the placeholder test script doesn't count
+    pub fn has_script(&self, name: &str) -> bool {
+        self.scripts
+            .get(name)
+            .is_some_and(|script| script != PLACEHOLDER_TEST)
+    }
+
+    /// The names of the scripts
+    pub fn script_names(&self) -> Vec<&str> {
+        self.scripts.keys().map(String::as_str).collect()
+    }
+
+    /// The major version of the package manager pinned for Corepack, like 4 for `yarn@4.1.0`
+    fn pinned_major(&self) -> Option<u32> {
+        let (_, version) = self.package_manager.as_deref()?.split_once('@')?;
+        version.split('.').next()?.parse().ok()
+    }
+}
+
+/// The commands of the node tasks, resolved once the project is loaded
+#[derive(Debug)]
+pub struct NodeCommands {
+    pub install: String,
+    pub dev: Option<String>,
+    pub build: Option<String>,
+    pub test: String,
+}
+
+/// Detect a Node.js package, managed with npm, pnpm or yarn
+pub async fn detect_node_project() -> Option<Project> {
+    if metadata("package.json").await.is_err() {
+        return None;
+    }
+    let package = read_package_json().await;
+    let manager = detect_package_manager(&package).await;
+
+    // `lox run` runs the `start` script, or the main file of the package
+    let run = start_command(manager, &package)
+        .await
+        .unwrap_or_else(|| String::from("unknown"));
+    let build = build_command(manager, &package)
+        .await
+        .unwrap_or_else(|| String::from("unknown"));
+
+    let mut project = Project::new(
+        manager,
+        package
+            .name
+            .clone()
+            .unwrap_or_else(|| String::from("unknown")),
+        package
+            .version
+            .clone()
+            .unwrap_or_else(|| String::from("unknown")),
+        BuildCommands {
+            dev: dev_command(manager, &package).unwrap_or_else(|| String::from("unknown")),
+            release: build,
+        },
+        RunCommands {
+            dev: run.clone(),
+            release: run,
+        },
+    );
+    project.commands = default_commands(manager, &package);
+    Some(project)
+}
+
+/// Read package.json, an empty one when it is missing or invalid
+pub async fn read_package_json() -> PackageJson {
+    let Ok(content) = read_to_string("package.json").await else {
+        return PackageJson::default();
+    };
+    match serde_json::from_str(&content) {
+        Ok(package) => package,
+        Err(e) => {
+            eprintln!("Warning: Failed to parse package.json: {}", e);
+            PackageJson::default()
+        }
+    }
+}
+
+/// Pick the package manager from the lockfile, then from `packageManager`, npm by default
+pub async fn detect_package_manager(package: &PackageJson) -> &'static str {
+    for (lockfile, manager) in LOCKFILES {
+        if metadata(lockfile).await.is_ok() {
+            return manager;
+        }
+    }
+    let pinned = package
+        .package_manager
+        .as_deref()
+        .and_then(|pinned| pinned.split('@').next())
+        .unwrap_or_default();
+    match pinned {
+        "pnpm" => "pnpm",
+        "yarn" => "yarn",
+        _ => "npm",
+    }
+}
+
+/// The command running a script of package.json
+pub fn script_command(manager: &str, script: &str) -> String {
+    format!("{} run {}", manager, script)
+}
+
+/// The command running a program installed in `node_modules/.bin`
+pub fn exec_command(manager: &str, program: &str) -> String {
+    match manager {
+        "pnpm" => format!("pnpm exec {}", program),
+        "yarn" => format!("yarn {}", program),
+        _ => format!("npx {}", program),
+    }
+}
+
+/// The command installing the dependencies
+pub fn install_command(manager: &str) -> String {
+    format!("{} install", manager)
+}
+
+/// The `dev` script, which `lox dev` runs after installing
+pub fn dev_command(manager: &str, package: &PackageJson) -> Option<String> {
+    package
+        .has_script("dev")
+        .then(|| script_command(manager, "dev"))
+}
+
+/// The `build` script, or the TypeScript compiler when there is a tsconfig.json
+pub async fn build_command(manager: &str, package: &PackageJson) -> Option<String> {
+    if package.has_script("build") {
+        Some(script_command(manager, "build"))
+    } else if metadata("tsconfig.json").await.is_ok() {
+        Some(exec_command(manager, "tsc"))
+    } else {
+        None
+    }
+}
+
+/// The `start` script, or node running the main file of the package
+pub async fn start_command(manager: &str, package: &PackageJson) -> Option<String> {
+    if package.has_script("start") {
+        return Some(script_command(manager, "start"));
+    }
+    let main = package.main.as_deref().unwrap_or("index.js");
+    metadata(main)
+        .await
+        .is_ok()
+        .then(|| format!("node {}", main))
+}
+
+/// The `test` script, or the test runner built into node
+pub fn test_command(manager: &str, package: &PackageJson) -> String {
+    if package.has_script("test") {
+        script_command(manager, "test")
+    } else {
+        String::from("node --test")
+    }
+}
+
+/// The commands of the node tasks of the package in the current directory
+pub async fn node_commands() -> NodeCommands {
+    let package = read_package_json().await;
+    let manager = detect_package_manager(&package).await;
+    NodeCommands {
+        install: install_command(manager),
+        dev: dev_command(manager, &package),
+        build: build_command(manager, &package).await,
+        test: test_command(manager, &package),
+    }
+}
+
+/// The `[project.commands]` of a package, its scripts or Prettier and ESLint
+pub fn default_commands(manager: &str, package: &PackageJson) -> ProjectCommands {
+    let script = |names: &[&str]| {
+        names
+            .iter()
+            .find(|name| package.has_script(name))
+            .map(|name| script_command(manager, name))
+    };
+
+    let (fmt, fmt_check) = match script(&["format", "fmt"]) {
+        Some(fmt) => (fmt, script(&["format:check", "fmt:check"])),
+        None => (
+            exec_command(manager, "prettier --write ."),
+            Some(exec_command(manager, "prettier --check .")),
+        ),
+    };
+    let (lint, lint_fix) = match script(&["lint"]) {
+        Some(lint) => (lint, script(&["lint:fix"])),
+        None => (
+            exec_command(manager, "eslint ."),
+            Some(exec_command(manager, "eslint . --fix")),
+        ),
+    };
+    // Yarn 2 and later renamed `yarn upgrade` to `yarn up`
+    let dependency = match script(&["update", "upgrade"]) {
+        Some(update) => update,
+        None if manager == "yarn" && package.pinned_major().is_some_and(|major| major >= 2) => {
+            String::from("yarn up")
+        }
+        None if manager == "yarn" => String::from("yarn upgrade"),
+        None => format!("{} update", manager),
+    };
+
+    ProjectCommands {
+        fmt,
+        fmt_check,
+        lint,
+        lint_fix,
+        dependency,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn package(content: &str) -> PackageJson {
+        serde_json::from_str(content).unwrap()
+    }
+
+    #[test]
+    fn yarn_dependency_command_follows_the_pinned_version() {
+        let classic = package(r#"{ "packageManager": "yarn@1.22.22" }"#);
+        assert_eq!(
+            default_commands("yarn", &classic).dependency,
+            "yarn upgrade"
+        );
+
+        let berry = package(r#"{ "packageManager": "yarn@4.5.0+sha512.abc" }"#);
+        assert_eq!(default_commands("yarn", &berry).dependency, "yarn up");
+
+        let unpinned = PackageJson::default();
+        assert_eq!(
+            default_commands("yarn", &unpinned).dependency,
+            "yarn upgrade"
+        );
+        assert_eq!(
+            default_commands("pnpm", &unpinned).dependency,
+            "pnpm update"
+        );
+    }
+
+    #[test]
+    fn update_script_replaces_the_dependency_command() {
+        let scripted = package(r#"{ "scripts": { "update": "yarn up -i" } }"#);
+        assert_eq!(
+            default_commands("yarn", &scripted).dependency,
+            "yarn run update"
+        );
+    }
+}
@@ -0,0 +1,34 @@
+use super::python::{PyProject, detect_entry_points, run_command};
+use super::{BuildCommands, Project, RunCommands};
+use tokio::fs::metadata;
+
+/// Detect a Python project managed with PDM
+pub async fn detect_pdm_project(pyproject: Option<&PyProject>) -> Option<Project> {
+    let pyproject = pyproject?;
+    let uses_pdm = pyproject.managing_tool() == Some("pdm") || metadata("pdm.lock").await.is_ok();
+    if !uses_pdm {
+        return None;
+    }
+
+    // Determine run commands in the PDM environment, `lox run <script>` picks among several
+    let command = match detect_entry_points(Some(pyproject)).await.default_target() {
+        Some(target) => run_command("pdm", &target),
+        None => String::from("unknown"),
+    };
+
+    Some(Project::new(
+        "pdm",
+        pyproject.name().unwrap_or_else(|| String::from("unknown")),
+        pyproject
+            .version()
+            .unwrap_or_else(|| String::from("unknown")),
+        BuildCommands {
+            dev: String::from("pdm build"),
+            release: String::from("pdm build"),
+        },
+        RunCommands {
+            dev: command.clone(),
+            release: command,
+        },
+    ))
+}
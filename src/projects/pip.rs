@@ -0,0 +1,134 @@
+use super::python::{PyProject, detect_entry_points, run_command};
+use super::{BuildCommands, Project, RunCommands, directory_name};
+use tokio::fs::{metadata, read_dir, read_to_string};
+
+/// Detect a Python project managed with pip and a virtual environment
+pub async fn detect_pip_project(pyproject: Option<&PyProject>) -> Option<Project> {
+    if !is_pip_managed(pyproject).await {
+        return None;
+    }
+
+    // Read the name and version from pyproject.toml, then from setup.py
+    let setup = read_to_string("setup.py").await.unwrap_or_default();
+    let project_name = pyproject
+        .and_then(PyProject::name)
+        .or_else(|| setup_value(&setup, "name"))
+        .or_else(directory_name)
+        .unwrap_or_else(|| String::from("unknown"));
+    let project_version = pyproject
+        .and_then(PyProject::version)
+        .or_else(|| setup_value(&setup, "version"))
+        .unwrap_or_else(|| String::from("unknown"));
+
+    // Determine run commands in the virtual environment, `lox run <script>` picks among several
+    let command = match detect_entry_points(pyproject).await.default_target() {
+        Some(target) => run_command("pip", &target),
+        None => String::from("unknown"),
+    };
+
+    Some(Project::new(
+        "pip",
+        project_name,
+        project_version,
+        BuildCommands {
+            dev: String::from("python -m build"),
+            release: String::from("python -m build"),
+        },
+        RunCommands {
+            dev: command.clone(),
+            release: command,
+        },
+    ))
+}
+
+/// Whether the current directory holds a Python project managed with pip instead of uv
+pub async fn is_pip_managed(pyproject: Option<&PyProject>) -> bool {
+    let has_setup = metadata("setup.py").await.is_ok();
+    let has_requirements = !requirement_files().await.is_empty();
+    uses_pip(pyproject, has_setup, has_requirements)
+}
+
+/// Whether a Python project is managed with pip, from what the directory holds
+///
+/// uv manages every pyproject.toml unless `[tool.lox]` sets `manager = "pip"`. Without a
+/// pyproject.toml, a `setup.py` or a `requirements*.txt` marks a pip project.
+fn uses_pip(pyproject: Option<&PyProject>, has_setup: bool, has_requirements: bool) -> bool {
+    match pyproject {
+        Some(pyproject) => pyproject.manager() == Some("pip"),
+        None => has_setup || has_requirements,
+    }
+}
+
+/// Find the `requirements*.txt` files next to the project, sorted by name
+pub async fn requirement_files() -> Vec<String> {
+    let mut files = Vec::new();
+    let Ok(mut entries) = read_dir(".").await else {
+        return files;
+    };
+    while let Ok(Some(entry)) = entries.next_entry().await {
+        let name = entry.file_name().to_string_lossy().to_string();
+        if name.starts_with("requirements") && name.ends_with(".txt") {
+            files.push(name);
+        }
+    }
+    files.sort();
+    files
+}
+
+/// Read a `key="value"` argument of the `setup()` call in setup.py
+fn setup_value(setup: &str, key: &str) -> Option<String> {
+    setup.lines().find_map(|line| {
+        let (name, value) = line.trim().split_once('=')?;
+        if name.trim() != key {
+            return None;
+        }
+        let value = value.trim().trim_end_matches(',');
+        let quoted = value
+            .strip_prefix('"')
+            .and_then(|value| value.strip_suffix('"'))
+            .or_else(|| {
+                value
+                    .strip_prefix('\'')
+                    .and_then(|value| value.strip_suffix('\''))
+            })?;
+        Some(quoted.to_string())
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn pyproject(content: &str) -> PyProject {
+        toml::from_str(content).unwrap()
+    }
+
+    #[test]
+    fn requirements_or_setup_without_pyproject_use_pip() {
+        assert!(uses_pip(None, false, true));
+        assert!(uses_pip(None, true, false));
+        assert!(uses_pip(None, true, true));
+        assert!(!uses_pip(None, false, false));
+    }
+
+    #[test]
+    fn pyproject_uses_uv_by_default() {
+        let setuptools = pyproject(
+            "[project]\nname = \"app\"\n\n[build-system]\nbuild-backend = \"setuptools.build_meta\"\n",
+        );
+        assert!(!uses_pip(Some(&setuptools), false, false));
+        assert!(!uses_pip(Some(&setuptools), true, false));
+        assert!(!uses_pip(Some(&setuptools), false, true));
+        assert!(!uses_pip(Some(&PyProject::default()), true, true));
+    }
+
+    #[test]
+    fn pyproject_with_the_pip_marker_uses_pip() {
+        let marked = pyproject("[project]\nname = \"app\"\n\n[tool.lox]\nmanager = \"pip\"\n");
+        assert!(uses_pip(Some(&marked), false, false));
+        assert!(uses_pip(Some(&marked), true, true));
+
+        let uv = pyproject("[tool.lox]\nmanager = \"uv\"\n");
+        assert!(!uses_pip(Some(&uv), false, true));
+    }
+}
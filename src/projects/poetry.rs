@@ -0,0 +1,35 @@
+use super::python::{PyProject, detect_entry_points, run_command};
+use super::{BuildCommands, Project, RunCommands};
+use tokio::fs::metadata;
+
+/// Detect a Python project managed with Poetry
+pub async fn detect_poetry_project(pyproject: Option<&PyProject>) -> Option<Project> {
+    let pyproject = pyproject?;
+    let uses_poetry =
+        pyproject.managing_tool() == Some("poetry") || metadata("poetry.lock").await.is_ok();
+    if !uses_poetry {
+        return None;
+    }
+
+    // Determine run commands in the Poetry environment, `lox run <script>` picks among several
+    let command = match detect_entry_points(Some(pyproject)).await.default_target() {
+        Some(target) => run_command("poetry", &target),
+        None => String::from("unknown"),
+    };
+
+    Some(Project::new(
+        "poetry",
+        pyproject.name().unwrap_or_else(|| String::from("unknown")),
+        pyproject
+            .version()
+            .unwrap_or_else(|| String::from("unknown")),
+        BuildCommands {
+            dev: String::from("poetry build"),
+            release: String::from("poetry build"),
+        },
+        RunCommands {
+            dev: command.clone(),
+            release: command,
+        },
+    ))
+}
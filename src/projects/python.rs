@@ -0,0 +1,306 @@
+use serde::Deserialize;
+use std::collections::BTreeMap;
+use std::env::consts::EXE_SUFFIX;
+use std::path::Path;
+use tokio::fs::{metadata, read_dir, read_to_string};
+
+/// The virtual environment of pip projects, the same directory uv uses
+pub const VENV_DIR: &str = ".venv";
+
+/// The parts of a pyproject.toml lox reads
+#[derive(Debug, Default, Deserialize)]
+pub struct PyProject {
+    pub project: Option<ProjectSection>,
+    #[serde(rename = "build-system")]
+    pub build_system: Option<BuildSystem>,
+    pub tool: Option<ToolSection>,
+}
+
+#[derive(Debug, Default, Deserialize)]
+pub struct ProjectSection {
+    pub name: Option<String>,
+    pub version: Option<String>,
+    #[serde(default)]
+    pub scripts: BTreeMap<String, String>,
+    #[serde(default, rename = "gui-scripts")]
+    pub gui_scripts: BTreeMap<String, String>,
+}
+
+#[derive(Debug, Default, Deserialize)]
+pub struct BuildSystem {
+    #[serde(rename = "build-backend")]
+    pub build_backend: Option<String>,
+}
+
+#[derive(Debug, Default, Deserialize)]
+pub struct ToolSection {
+    pub lox: Option<LoxSection>,
+    pub poetry: Option<PoetrySection>,
+    pub pdm: Option<toml::Value>,
+    pub mypy: Option<toml::Value>,
+    pub pyright: Option<toml::Value>,
+}
+
+/// `[tool.poetry]`, which holds the metadata of projects made before Poetry 2
+#[derive(Debug, Default, Deserialize)]
+pub struct PoetrySection {
+    pub name: Option<String>,
+    pub version: Option<String>,
+    /// A module path, or a table with a `reference` for the newer script kinds
+    #[serde(default)]
+    pub scripts: BTreeMap<String, toml::Value>,
+}
+
+#[derive(Debug, Default, Deserialize)]
+pub struct LoxSection {
+    /// What runs the project, like `app` or `python -m app`
+    pub entry: Option<String>,
+    /// The tool managing the project, `pip` for a pyproject.toml uv should not manage
+    pub manager: Option<String>,
+}
+
+/// A way to run a Python project
+#[derive(Debug, Clone)]
+pub struct EntryPoint {
+    /// The name picked with `lox run <script>`
+    pub name: String,
+    /// The program and arguments to run in the environment, like `python -m app`
+    pub target: String,
+}
+
+/// The ways to run a Python project, found in pyproject.toml and the sources
+#[derive(Debug, Default)]
+pub struct EntryPoints {
+    /// The target of `tool.lox.entry`
+    pub entry: Option<String>,
+    /// The `[project.scripts]` and `[project.gui-scripts]`, or a `__main__.py` or `main.py`
+    pub scripts: Vec<EntryPoint>,
+}
+
+impl EntryPoints {
+    /// The target `lox run` runs without a script: `tool.lox.entry`, or the only script
+    pub fn default_target(&self) -> Option<String> {
+        if let Some(entry) = &self.entry {
+            return Some(entry.clone());
+        }
+        match self.scripts.as_slice() {
+            [script] => Some(script.target.clone()),
+            _ => None,
+        }
+    }
+
+    /// The entry point with a name
+    pub fn find(&self, name: &str) -> Option<&EntryPoint> {
+        self.scripts.iter().find(|script| script.name == name)
+    }
+
+    /// The names of the entry points
+    pub fn names(&self) -> Vec<&str> {
+        self.scripts
+            .iter()
+            .map(|script| script.name.as_str())
+            .collect()
+    }
+}
+
+/// A type checker configured for the project
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum TypeChecker {
+    Mypy,
+    Pyright,
+}
+
+/// Read pyproject.toml, `None` when it is missing and an empty one when it is invalid
+///
+/// Detection reads it once and passes it down, so a broken file is only reported once.
+pub async fn read_pyproject() -> Option<PyProject> {
+    let content = read_to_string("pyproject.toml").await.ok()?;
+    match toml::from_str(&content) {
+        Ok(pyproject) => Some(pyproject),
+        Err(e) => {
+            eprintln!("Warning: Failed to parse pyproject.toml: {}", e.message());
+            Some(PyProject::default())
+        }
+    }
+}
+
+impl PyProject {
+    /// The name of the project, from `[project]` or `[tool.poetry]`
+    pub fn name(&self) -> Option<String> {
+        self.project
+            .as_ref()
+            .and_then(|project| project.name.clone())
+            .or_else(|| self.poetry().and_then(|poetry| poetry.name.clone()))
+    }
+
+    /// The version of the project, from `[project]` or `[tool.poetry]`
+    pub fn version(&self) -> Option<String> {
+        self.project
+            .as_ref()
+            .and_then(|project| project.version.clone())
+            .or_else(|| self.poetry().and_then(|poetry| poetry.version.clone()))
+    }
+
+    /// The tool `[tool.lox]` names as the manager of the project
+    pub fn manager(&self) -> Option<&str> {
+        self.tool
+            .as_ref()
+            .and_then(|tool| tool.lox.as_ref())
+            .and_then(|lox| lox.manager.as_deref())
+    }
+
+    fn poetry(&self) -> Option<&PoetrySection> {
+        self.tool.as_ref().and_then(|tool| tool.poetry.as_ref())
+    }
+
+    /// The tool managing the project, when its tool section or build backend names one
+    ///
+    /// Returns the project type, `poetry` or `pdm`.
+    pub fn managing_tool(&self) -> Option<&'static str> {
+        let backend = self
+            .build_system
+            .as_ref()
+            .and_then(|build_system| build_system.build_backend.as_deref())
+            .unwrap_or_default();
+        let tool = self.tool.as_ref();
+
+        if tool.is_some_and(|tool| tool.poetry.is_some()) || backend.starts_with("poetry") {
+            Some("poetry")
+        } else if tool.is_some_and(|tool| tool.pdm.is_some()) || backend.starts_with("pdm") {
+            Some("pdm")
+        } else {
+            None
+        }
+    }
+}
+
+/// The command running an entry point target with the tool of a project type
+pub fn run_command(project_type: &str, target: &str) -> String {
+    if matches!(project_type, "uv" | "poetry" | "pdm") {
+        return format!("{} run {}", project_type, target);
+    }
+
+    // Scripts and the interpreter both live in the bin directory of the environment
+    let mut words = shlex::split(target).unwrap_or_else(|| vec![target.to_string()]);
+    match words.first_mut() {
+        Some(script) if script.ends_with(".py") => words.insert(0, venv_program("python")),
+        Some(program) => *program = venv_program(program),
+        None => return venv_program("python"),
+    }
+    shlex::try_join(words.iter().map(String::as_str)).unwrap_or_else(|_| words.join(" "))
+}
+
+/// The path of a program installed in the virtual environment
+pub fn venv_program(program: &str) -> String {
+    let bin = if cfg!(windows) { "Scripts" } else { "bin" };
+    format!("{}/{}/{}{}", VENV_DIR, bin, program, EXE_SUFFIX)
+}
+
+/// Find the type checker configured in pyproject.toml or in its own config file
+pub async fn detect_type_checker(pyproject: Option<&PyProject>) -> Option<TypeChecker> {
+    let tool = pyproject.and_then(|pyproject| pyproject.tool.as_ref());
+    let has_mypy = tool.is_some_and(|tool| tool.mypy.is_some())
+        || metadata("mypy.ini").await.is_ok()
+        || metadata(".mypy.ini").await.is_ok();
+    let has_pyright = tool.is_some_and(|tool| tool.pyright.is_some())
+        || metadata("pyrightconfig.json").await.is_ok();
+
+    if has_mypy {
+        Some(TypeChecker::Mypy)
+    } else if has_pyright {
+        Some(TypeChecker::Pyright)
+    } else {
+        None
+    }
+}
+
+/// Find the ways to run the Python project in the current directory
+pub async fn detect_entry_points(pyproject: Option<&PyProject>) -> EntryPoints {
+    let name = pyproject.and_then(PyProject::name);
+    let entry = pyproject
+        .and_then(|pyproject| pyproject.tool.as_ref())
+        .and_then(|tool| tool.lox.as_ref())
+        .and_then(|lox| lox.entry.clone());
+    let poetry_scripts = pyproject
+        .and_then(PyProject::poetry)
+        .map(|poetry| poetry.scripts.keys());
+    let section = pyproject.and_then(|pyproject| pyproject.project.as_ref());
+
+    // Installing the project puts its scripts into the environment
+    let mut scripts: Vec<EntryPoint> = section
+        .into_iter()
+        .flat_map(|section| section.scripts.keys().chain(section.gui_scripts.keys()))
+        .chain(poetry_scripts.into_iter().flatten())
+        .map(|name| EntryPoint {
+            name: name.clone(),
+            target: name.clone(),
+        })
+        .collect();
+
+    if scripts.is_empty() {
+        let package = name.map(|name| name.replace('-', "_"));
+        if let Some(module) = find_main_module(package.as_deref()).await {
+            scripts.push(EntryPoint {
+                target: format!("python -m {}", module),
+                name: module,
+            });
+        } else if metadata("main.py").await.is_ok() {
+            scripts.push(EntryPoint {
+                name: String::from("main.py"),
+                target: String::from("main.py"),
+            });
+        }
+    }
+
+    EntryPoints { entry, scripts }
+}
+
+/// Find a package with a `__main__.py`, in `src/` or next to pyproject.toml
+///
+/// The package named after the project wins over the others.
+async fn find_main_module(package: Option<&str>) -> Option<String> {
+    let mut modules = Vec::new();
+    for dir in ["src", "."] {
+        let Ok(mut entries) = read_dir(dir).await else {
+            continue;
+        };
+        while let Ok(Some(entry)) = entries.next_entry().await {
+            let name = entry.file_name().to_string_lossy().to_string();
+            if !name.starts_with('.') && has_main(&entry.path()).await {
+                modules.push(name);
+            }
+        }
+    }
+    modules.sort();
+
+    match package {
+        Some(package) if modules.iter().any(|module| module == package) => {
+            Some(package.to_string())
+        }
+        _ => modules.into_iter().next(),
+    }
+}
+
+async fn has_main(dir: &Path) -> bool {
+    metadata(dir.join("__main__.py")).await.is_ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn run_command_keeps_quoted_arguments() {
+        let python = venv_program("python");
+        assert_eq!(
+            run_command("pip", "python -m app 'two words'"),
+            format!("{} -m app 'two words'", python)
+        );
+        assert_eq!(
+            run_command("pip", "main.py --name \"a b\""),
+            format!("{} main.py --name 'a b'", python)
+        );
+        assert_eq!(run_command("pip", "serve"), venv_program("serve"));
+        assert_eq!(run_command("uv", "python -m app"), "uv run python -m app");
+    }
+}
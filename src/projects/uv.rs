@@ -1,34 +1,15 @@
-use super::{BuildCommands, Project, RunCommands};
-use tokio::fs::{metadata, read_to_string};
+use super::python::{PyProject, detect_entry_points, run_command};
+use super::{BuildCommands, Pipelines, Project, ProjectCommands, RunCommands, WatchConfig};
 
-pub async fn detect_uv_project() -> Option<Project> {
+pub async fn detect_uv_project(pyproject: Option<&PyProject>) -> Option<Project> {
     // Check if it's a Python project (has pyproject.toml)
-    if !metadata("pyproject.toml").await.is_ok() {
-        return None;
-    }
+    let pyproject = pyproject?;
 
     let project_type = String::from("uv");
-    let mut project_name = String::from("unknown");
-    let mut project_version = String::from("unknown");
-    let is_library = false;
-
-    if let Ok(pyproject_content) = read_to_string("pyproject.toml").await {
-        for line in pyproject_content.lines() {
-            if line.starts_with("name = ") {
-                if let Some((_, rest)) = line.split_once('"') {
-                    if let Some((name, _)) = rest.split_once('"') {
-                        project_name = name.to_string();
-                    }
-                }
-            } else if line.starts_with("version = ") {
-                if let Some((_, rest)) = line.split_once('"') {
-                    if let Some((version, _)) = rest.split_once('"') {
-                        project_version = version.to_string();
-                    }
-                }
-            }
-        }
-    }
+    let project_name = pyproject.name().unwrap_or_else(|| String::from("unknown"));
+    let project_version = pyproject
+        .version()
+        .unwrap_or_else(|| String::from("unknown"));
 
     // Determine build commands for Python projects
     let build_commands = BuildCommands {
@@ -36,21 +17,28 @@ pub async fn detect_uv_project() -> Option<Project> {
         release: String::from("uv build"),
     };
 
-    // Determine run commands for Python projects
+    // Determine run commands for Python projects, `lox run <script>` picks among several
+    let command = match detect_entry_points(Some(pyproject)).await.default_target() {
+        Some(target) => run_command("uv", &target),
+        None => String::from("unknown"),
+    };
     let run_commands = RunCommands {
-        dev: String::from("uv run main.py"),
-        release: String::from("uv run main.py"),
+        dev: command.clone(),
+        release: command,
     };
 
     Some(Project {
         project_type,
         name: project_name,
         version: project_version,
-        is_library,
         build_commands,
         run_commands,
+        commands: ProjectCommands::defaults("uv"),
         is_rust_project: false,
         is_uv_project: true,
         is_fortran_project: false,
+        user_tasks: Vec::new(),
+        pipelines: Pipelines::default(),
+        watch: WatchConfig::default(),
     })
 }
@@ -0,0 +1,87 @@
+use super::{BuildCommands, Executable, Project, RunCommands, command_path, directory_name};
+use std::env::consts::EXE_EXTENSION;
+use std::path::Path;
+use tokio::fs::{read_dir, read_to_string};
+
+/// Where `zig build` installs the artifacts, both profiles share it
+pub const OUT_DIR: &str = "zig-out";
+/// Where `zig build` installs the executables
+pub const BIN_DIR: &str = "zig-out/bin";
+
+/// The arguments of `zig build` for a profile, the default install step
+pub const DEV_ARGS: [&str; 1] = ["build"];
+pub const RELEASE_ARGS: [&str; 2] = ["build", "-Doptimize=ReleaseFast"];
+
+/// Detect a Zig project, from its build.zig or its build.zig.zon
+pub async fn detect_zig_project() -> Option<Project> {
+    let manifest = read_to_string("build.zig.zon").await.ok();
+    if manifest.is_none() && !Path::new("build.zig").exists() {
+        return None;
+    }
+    let manifest = manifest.unwrap_or_default();
+
+    let name = manifest_field(&manifest, ".name")
+        .or_else(directory_name)
+        .unwrap_or_else(|| String::from("unknown"));
+
+    // The executables are only known once the project is installed to `zig-out`
+    let run = match executables().await.as_deref() {
+        Some([executable]) => executable.path.clone(),
+        _ => String::from("unknown"),
+    };
+
+    Some(Project::new(
+        "zig",
+        name,
+        manifest_field(&manifest, ".version").unwrap_or_else(|| String::from("unknown")),
+        BuildCommands {
+            dev: format!("zig {}", DEV_ARGS.join(" ")),
+            release: format!("zig {}", RELEASE_ARGS.join(" ")),
+        },
+        RunCommands {
+            dev: run.clone(),
+            release: run,
+        },
+    ))
+}
+
+/// Find the executables installed to `zig-out/bin`, sorted by name
+///
+/// Returns `None` when the project is not installed yet.
+pub async fn executables() -> Option<Vec<Executable>> {
+    let mut entries = read_dir(BIN_DIR).await.ok()?;
+    let mut executables = Vec::new();
+    while let Ok(Some(entry)) = entries.next_entry().await {
+        let path = entry.path();
+        if !path.is_file() {
+            continue;
+        }
+        // Debug information like `app.pdb` is installed next to the Windows executables
+        let extension = path.extension().unwrap_or_default();
+        if extension != EXE_EXTENSION {
+            continue;
+        }
+        if let Some(name) = path.file_stem() {
+            executables.push(Executable {
+                name: name.to_string_lossy().to_string(),
+                path: command_path(&Path::new(".").join(&path)),
+            });
+        }
+    }
+    executables.sort_by(|a, b| a.name.cmp(&b.name));
+    Some(executables)
+}
+
+/// Read a string or enum literal field of build.zig.zon, like `.name = "app"` or `.name = .app`
+fn manifest_field(manifest: &str, field: &str) -> Option<String> {
+    manifest.lines().find_map(|line| {
+        let value = line
+            .trim()
+            .strip_prefix(field)?
+            .trim_start()
+            .strip_prefix('=')?;
+        let value = value.trim().trim_end_matches(',').trim();
+        let value = value.trim_start_matches('.').trim_matches('"');
+        (!value.is_empty()).then(|| value.to_string())
+    })
+}
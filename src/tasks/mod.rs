@@ -1,7 +1,11 @@
 // Re-export the task system API for external use
 
 // Public API functions
-pub use self::task::execute_task_by_id;
+pub use self::pipeline::{PipelineKind, StepFailure, resolve_pipeline, run_stages};
+pub use self::task::{
+    execute_command, execute_tasks, list_tasks, register_node_tasks, register_user_tasks,
+    set_cargo_package, set_jobs, test_counts,
+};
 
 // Re-export task IDs for easy access
 pub const UV_LOCK: &str = "uv_lock";
@@ -15,7 +19,46 @@ pub const CARGO_BUILD: &str = "cargo_build";
 pub const CARGO_BUILD_RELEASE: &str = "cargo_build_release";
 pub const FLANG_BUILD_DEV: &str = "flang_build_dev";
 pub const FLANG_BUILD_RELEASE: &str = "flang_build_release";
+pub const FPM_BUILD: &str = "fpm_build";
 pub const FPM_BUILD_RELEASE: &str = "fpm_build_release";
+pub const CARGO_TEST: &str = "cargo_test";
+pub const UV_PYTEST: &str = "uv_pytest";
+pub const FPM_TEST: &str = "fpm_test";
+pub const FLANG_TEST: &str = "flang_test";
+pub const UV_SYNC_DEV: &str = "uv_sync_dev";
+pub const UV_MYPY: &str = "uv_mypy";
+pub const UV_PYRIGHT: &str = "uv_pyright";
+pub const PIP_VENV: &str = "pip_venv";
+pub const PIP_INSTALL: &str = "pip_install";
+pub const PIP_INSTALL_BUILD: &str = "pip_install_build";
+pub const PIP_BUILD: &str = "pip_build";
+pub const POETRY_INSTALL: &str = "poetry_install";
+pub const POETRY_BUILD: &str = "poetry_build";
+pub const PDM_INSTALL: &str = "pdm_install";
+pub const PDM_BUILD: &str = "pdm_build";
+pub const CONDA_ENV: &str = "conda_env";
+pub const CONDA_UPDATE: &str = "conda_update";
+pub const PIXI_INSTALL: &str = "pixi_install";
+pub const ENV_BUILD: &str = "env_build";
+pub const ENV_BUILD_RELEASE: &str = "env_build_release";
+pub const CMAKE_CONFIGURE: &str = "cmake_configure";
+pub const CMAKE_CONFIGURE_RELEASE: &str = "cmake_configure_release";
+pub const CMAKE_BUILD: &str = "cmake_build";
+pub const CMAKE_BUILD_RELEASE: &str = "cmake_build_release";
+pub const CMAKE_TEST: &str = "cmake_test";
+pub const GO_BUILD: &str = "go_build";
+pub const GO_BUILD_RELEASE: &str = "go_build_release";
+pub const GO_TEST: &str = "go_test";
+pub const ZIG_BUILD: &str = "zig_build";
+pub const ZIG_BUILD_RELEASE: &str = "zig_build_release";
+pub const ZIG_TEST: &str = "zig_test";
+pub const CUSTOM_BUILD: &str = "custom_build";
+pub const CUSTOM_BUILD_RELEASE: &str = "custom_build_release";
+pub const NODE_INSTALL: &str = "node_install";
+pub const NODE_DEV: &str = "node_dev";
+pub const NODE_BUILD: &str = "node_build";
+pub const NODE_TEST: &str = "node_test";
 
-// Private module containing the implementation
+// Private modules containing the implementation
+mod pipeline;
 mod task;
@@ -0,0 +1,308 @@
+use crate::projects::python::{TypeChecker, detect_type_checker, read_pyproject};
+use crate::projects::{
+    Pipeline, Project, Stage, custom, is_custom_type, is_native_type, is_node_type, is_runner_type,
+    make, node,
+};
+use crate::tasks::{self, execute_tasks};
+
+// The commands that run a pipeline
+#[derive(Debug, Clone, Copy)]
+pub enum PipelineKind {
+    Dev,
+    Build,
+    Run,
+    Test,
+}
+
+// Create a stage from a name and a list of task IDs
+fn stage(name: &str, task_ids: &[&str]) -> Stage {
+    Stage {
+        name: name.to_string(),
+        tasks: task_ids.iter().map(|id| id.to_string()).collect(),
+        continue_on_error: false,
+    }
+}
+
+// Get the built-in pipeline of a project type
+async fn default_pipeline(project: &Project, kind: PipelineKind) -> Option<Pipeline> {
+    let is_fpm = project.project_type == "fpm";
+    let is_pip = project.project_type == "pip";
+    // Poetry and PDM install and build the same way
+    let (tool_install, tool_build) = match project.project_type.as_str() {
+        "poetry" => (tasks::POETRY_INSTALL, tasks::POETRY_BUILD),
+        "pdm" => (tasks::PDM_INSTALL, tasks::PDM_BUILD),
+        _ => ("", ""),
+    };
+    let is_python_tool = !tool_install.is_empty();
+    // conda and pixi prepare the environment, then build the project inside it
+    let (env_prepare, env_update) = match project.project_type.as_str() {
+        "conda" => (tasks::CONDA_ENV, tasks::CONDA_UPDATE),
+        "pixi" => (tasks::PIXI_INSTALL, tasks::PIXI_INSTALL),
+        _ => ("", ""),
+    };
+    let is_environment = !env_prepare.is_empty();
+    let is_cmake = project.project_type == "cmake";
+    let is_go = project.project_type == "go";
+    let is_zig = project.project_type == "zig";
+    let is_node = is_node_type(&project.project_type);
+    // Makefile and justfile projects map the conventional targets onto the lox commands
+    let is_runner = is_runner_type(&project.project_type);
+    let build_target = make::find_target(project, &make::BUILD_TARGETS);
+    let test_target = make::find_target(project, &[make::TEST_TARGET]);
+    // Custom projects run the tasks made of their lox.toml commands, when they are set
+    let is_custom = is_custom_type(&project.project_type);
+    let custom_task = |name: &str| custom::find_task(project, name).map(|task| task.name.as_str());
+
+    let stages = match kind {
+        PipelineKind::Dev if project.is_rust_project => vec![
+            stage(
+                "Download dependencies",
+                &[tasks::CARGO_UPDATE, tasks::CARGO_FMT],
+            ),
+            stage("Check the project", &[tasks::CARGO_CHECK]),
+            stage("Build the project", &[tasks::CARGO_BUILD]),
+        ],
+        PipelineKind::Dev if project.is_uv_project => {
+            let mut stages = vec![
+                stage("Sync the environment", &[tasks::UV_SYNC_DEV]),
+                stage(
+                    "Check and Format the project",
+                    &[tasks::UV_RUFF_CHECK, tasks::UV_RUFF_FORMAT],
+                ),
+            ];
+            // Type check only when the project configures a type checker
+            match detect_type_checker(read_pyproject().await.as_ref()).await {
+                Some(TypeChecker::Mypy) => {
+                    stages.push(stage("Type check the project", &[tasks::UV_MYPY]))
+                }
+                Some(TypeChecker::Pyright) => {
+                    stages.push(stage("Type check the project", &[tasks::UV_PYRIGHT]))
+                }
+                None => {}
+            }
+            stages
+        }
+        PipelineKind::Dev if is_pip => vec![
+            stage("Create the environment", &[tasks::PIP_VENV]),
+            stage("Install the dependencies", &[tasks::PIP_INSTALL]),
+        ],
+        PipelineKind::Dev | PipelineKind::Run if is_python_tool => {
+            vec![stage("Install the dependencies", &[tool_install])]
+        }
+        PipelineKind::Build if is_python_tool => vec![
+            stage("Install the dependencies", &[tool_install]),
+            stage("Build the project", &[tool_build]),
+        ],
+        PipelineKind::Dev if is_environment => vec![
+            stage("Update the environment", &[env_update]),
+            stage("Build the project", &[tasks::ENV_BUILD]),
+        ],
+        PipelineKind::Build if is_environment => vec![
+            stage("Create the environment", &[env_prepare]),
+            stage("Build the project", &[tasks::ENV_BUILD_RELEASE]),
+        ],
+        PipelineKind::Run if is_environment => {
+            vec![stage("Create the environment", &[env_prepare])]
+        }
+        PipelineKind::Dev if is_cmake => vec![
+            stage("Configure the project", &[tasks::CMAKE_CONFIGURE]),
+            stage("Build the project", &[tasks::CMAKE_BUILD]),
+        ],
+        PipelineKind::Build if is_cmake => vec![
+            stage("Configure the project", &[tasks::CMAKE_CONFIGURE_RELEASE]),
+            stage("Build the project", &[tasks::CMAKE_BUILD_RELEASE]),
+        ],
+        PipelineKind::Test if is_cmake => vec![
+            stage("Configure the project", &[tasks::CMAKE_CONFIGURE]),
+            stage("Build the project", &[tasks::CMAKE_BUILD]),
+            stage("Test the project", &[tasks::CMAKE_TEST]),
+        ],
+        PipelineKind::Dev if is_go => vec![stage("Build the project", &[tasks::GO_BUILD])],
+        PipelineKind::Build if is_go => {
+            vec![stage("Build the project", &[tasks::GO_BUILD_RELEASE])]
+        }
+        PipelineKind::Test if is_go => vec![stage("Test the project", &[tasks::GO_TEST])],
+        PipelineKind::Dev if is_zig => vec![stage("Build the project", &[tasks::ZIG_BUILD])],
+        PipelineKind::Build if is_zig => {
+            vec![stage("Build the project", &[tasks::ZIG_BUILD_RELEASE])]
+        }
+        PipelineKind::Test if is_zig => vec![stage("Test the project", &[tasks::ZIG_TEST])],
+        PipelineKind::Dev | PipelineKind::Build if is_runner => {
+            vec![stage("Build the project", &[build_target?])]
+        }
+        PipelineKind::Test if is_runner => vec![stage("Test the project", &[test_target?])],
+        // The `run` target builds what it needs itself
+        PipelineKind::Run if is_runner => vec![],
+        PipelineKind::Dev if is_custom => {
+            vec![stage(
+                "Build the project",
+                &[custom_task(tasks::CUSTOM_BUILD)?],
+            )]
+        }
+        PipelineKind::Build if is_custom => vec![stage(
+            "Build the project",
+            &[custom_task(tasks::CUSTOM_BUILD_RELEASE)?],
+        )],
+        PipelineKind::Test if is_custom => {
+            vec![stage(
+                "Test the project",
+                &[custom_task(custom::TEST_TASK)?],
+            )]
+        }
+        PipelineKind::Run if is_custom => vec![],
+        // Node.js packages run their scripts, when package.json has them
+        PipelineKind::Dev | PipelineKind::Build if is_node => {
+            let commands = node::node_commands().await;
+            let mut stages = vec![stage("Install the dependencies", &[tasks::NODE_INSTALL])];
+            match kind {
+                PipelineKind::Dev if commands.dev.is_some() => {
+                    stages.push(stage("Run the dev script", &[tasks::NODE_DEV]))
+                }
+                PipelineKind::Build if commands.build.is_some() => {
+                    stages.push(stage("Build the project", &[tasks::NODE_BUILD]))
+                }
+                _ => {}
+            }
+            stages
+        }
+        PipelineKind::Run if is_node => {
+            vec![stage("Install the dependencies", &[tasks::NODE_INSTALL])]
+        }
+        PipelineKind::Test if is_node => vec![stage("Test the project", &[tasks::NODE_TEST])],
+        PipelineKind::Dev if project.is_fortran_project => vec![stage(
+            "Build the project",
+            &[if is_fpm {
+                tasks::FPM_BUILD
+            } else {
+                tasks::FLANG_BUILD_DEV
+            }],
+        )],
+        PipelineKind::Build if project.is_rust_project => vec![
+            stage(
+                "Download dependencies",
+                &[tasks::CARGO_UPDATE, tasks::CARGO_FMT],
+            ),
+            stage("Check the project", &[tasks::CARGO_CHECK]),
+            stage("Build the project", &[tasks::CARGO_BUILD_RELEASE]),
+        ],
+        PipelineKind::Build if project.is_uv_project => vec![
+            stage("Lock the project dependencies", &[tasks::UV_LOCK]),
+            stage(
+                "Check and Format the project",
+                &[tasks::UV_RUFF_CHECK, tasks::UV_RUFF_FORMAT],
+            ),
+            stage("Build the project", &[tasks::UV_BUILD]),
+        ],
+        PipelineKind::Build if is_pip => vec![
+            stage("Create the environment", &[tasks::PIP_VENV]),
+            stage("Install the dependencies", &[tasks::PIP_INSTALL]),
+            stage("Install the build frontend", &[tasks::PIP_INSTALL_BUILD]),
+            stage("Build the project", &[tasks::PIP_BUILD]),
+        ],
+        PipelineKind::Build if project.is_fortran_project => vec![stage(
+            "Build the project",
+            &[if is_fpm {
+                tasks::FPM_BUILD_RELEASE
+            } else {
+                tasks::FLANG_BUILD_RELEASE
+            }],
+        )],
+        // Rust, Fortran and native binaries are built on demand by `lox run` itself
+        PipelineKind::Run
+            if project.is_rust_project
+                || project.is_fortran_project
+                || is_native_type(&project.project_type) =>
+        {
+            vec![]
+        }
+        PipelineKind::Run if project.is_uv_project => {
+            vec![stage("Lock the project dependencies.", &[tasks::UV_LOCK])]
+        }
+        PipelineKind::Run if is_pip => vec![
+            stage("Create the environment", &[tasks::PIP_VENV]),
+            stage("Install the dependencies", &[tasks::PIP_INSTALL]),
+        ],
+        PipelineKind::Test if project.is_rust_project => {
+            vec![stage("Test the project", &[tasks::CARGO_TEST])]
+        }
+        PipelineKind::Test if project.is_uv_project => {
+            vec![stage("Test the project", &[tasks::UV_PYTEST])]
+        }
+        PipelineKind::Test if project.is_fortran_project => vec![stage(
+            "Test the project",
+            &[if is_fpm {
+                tasks::FPM_TEST
+            } else {
+                tasks::FLANG_TEST
+            }],
+        )],
+        _ => return None,
+    };
+
+    Some(Pipeline { stages })
+}
+
+// Get the pipeline configured in lox.toml, or the built-in one for the project type
+pub async fn resolve_pipeline(project: &Project, kind: PipelineKind) -> Option<Pipeline> {
+    let configured = match kind {
+        PipelineKind::Dev => &project.pipelines.dev,
+        PipelineKind::Build => &project.pipelines.build,
+        PipelineKind::Run => &project.pipelines.run,
+        PipelineKind::Test => &project.pipelines.test,
+    };
+    match configured {
+        Some(pipeline) => Some(pipeline.clone()),
+        None => default_pipeline(project, kind).await,
+    }
+}
+
+// The step a pipeline stopped at, and the tasks that failed in it
+#[derive(Debug)]
+pub struct StepFailure {
+    pub step: String,
+    pub tasks: Vec<String>,
+}
+
+impl StepFailure {
+    pub fn new(index: usize, total: usize, name: &str, tasks: Vec<String>) -> Self {
+        Self {
+            step: format!("[{}/{}] {}", index, total, name),
+            tasks,
+        }
+    }
+
+    // Print the final summary naming the failing step
+    pub fn report(&self) {
+        eprintln!(
+            "[ERROR] + Failed at `{}`: {}.",
+            self.step,
+            self.tasks.join(", ")
+        );
+    }
+}
+
+// Run the stages of a pipeline in order, numbered out of `total` stages
+//
+// Stops at the first failed stage, unless it is marked `continue_on_error`.
+pub async fn run_stages(stages: &[Stage], total: usize) -> Result<(), StepFailure> {
+    for (index, stage) in stages.iter().enumerate() {
+        println!("[{}/{}] + {}", index + 1, total, stage.name);
+        let result = execute_tasks(&stage.tasks).await;
+        println!();
+
+        if let Err(failed_tasks) = result {
+            if stage.continue_on_error {
+                println!("[TIP] + `{}` failed, continuing.", stage.name);
+                println!();
+            } else {
+                return Err(StepFailure::new(
+                    index + 1,
+                    total,
+                    &stage.name,
+                    failed_tasks,
+                ));
+            }
+        }
+    }
+    Ok(())
+}
@@ -1,10 +1,20 @@
+use crate::projects::UserTask;
+use crate::projects::python::{self, VENV_DIR, venv_program};
+use crate::projects::{cmake, conda, go, node, zig};
+use std::cell::RefCell;
+use std::collections::HashSet;
 use std::future::Future;
 use std::io::{Write, stdin, stdout};
+use std::path::Path;
 use std::pin::Pin;
+use std::process::Stdio;
+use std::sync::{Mutex, OnceLock};
+use tokio::io::{AsyncBufReadExt, AsyncRead, BufReader};
 use tokio::process::Command;
+use tokio::task::JoinSet;
 
 // Define a type alias for our async task function
-pub type TaskFn = dyn Fn() -> Pin<Box<dyn Future<Output = bool>>> + Send + Sync;
+pub type TaskFn = dyn Fn() -> Pin<Box<dyn Future<Output = bool> + Send>> + Send + Sync;
 
 // Define a Task struct that encapsulates task logic
 struct Task {
@@ -12,6 +22,10 @@ struct Task {
     id: String,
     // Display name of the task
     name: String,
+    // Optional description shown by `lox task --list`
+    description: Option<String>,
+    // IDs of the tasks that must succeed before this one starts
+    depends_on: Vec<String>,
     // Async function to execute the task
     execute: Box<TaskFn>,
 }
@@ -21,15 +35,29 @@ impl Task {
     fn new<F, Fut>(id: &str, name: &str, execute: F) -> Self
     where
         F: Fn() -> Fut + Send + Sync + 'static,
-        Fut: Future<Output = bool> + 'static,
+        Fut: Future<Output = bool> + Send + 'static,
     {
         Self {
             id: id.to_string(),
             name: name.to_string(),
+            description: None,
+            depends_on: Vec::new(),
             execute: Box::new(move || Box::pin(execute())),
         }
     }
 
+    // Attach a description to the task
+    fn with_description(mut self, description: Option<String>) -> Self {
+        self.description = description;
+        self
+    }
+
+    // Declare the tasks this one depends on
+    fn with_dependencies(mut self, depends_on: &[String]) -> Self {
+        self.depends_on = depends_on.to_vec();
+        self
+    }
+
     // Get the unique task ID
     fn id(&self) -> &str {
         &self.id
@@ -40,9 +68,14 @@ impl Task {
         &self.name
     }
 
-    // Execute the task and return its status
-    async fn execute(&self) -> bool {
-        (self.execute)().await
+    // Get the description of the task, falling back to its display name
+    fn description(&self) -> &str {
+        self.description.as_deref().unwrap_or(&self.name)
+    }
+
+    // Start the task, the returned future owns everything it needs
+    fn execute(&self) -> Pin<Box<dyn Future<Output = bool> + Send>> {
+        (self.execute)()
     }
 
     // Get the message to display after task completion
@@ -55,6 +88,165 @@ impl Task {
     }
 }
 
+// Helper functions for task output
+
+tokio::task_local! {
+    // Output of the running task, set when it runs next to other tasks
+    static TASK_OUTPUT: RefCell<String>;
+}
+
+// Maximum number of tasks running at the same time
+static JOBS: OnceLock<usize> = OnceLock::new();
+
+// Get the job limit, defaulting to the number of CPUs
+fn jobs() -> usize {
+    *JOBS.get_or_init(|| {
+        std::thread::available_parallelism()
+            .map(|jobs| jobs.get())
+            .unwrap_or(1)
+    })
+}
+
+// Print a line of task output, or buffer it when the task output is buffered
+fn task_println(line: &str) {
+    let buffered = TASK_OUTPUT.try_with(|output| {
+        let mut output = output.borrow_mut();
+        output.push_str(line);
+        output.push('\n');
+    });
+    if buffered.is_err() {
+        println!("{}", line);
+    }
+}
+
+// Run a command and return whether it succeeded, capturing its output when buffered
+async fn run_command(command: &mut Command, error_message: &str) -> bool {
+    let result = if TASK_OUTPUT.try_with(|_| ()).is_ok() {
+        command.output().await.map(|output| {
+            TASK_OUTPUT.with(|buffer| {
+                let mut buffer = buffer.borrow_mut();
+                buffer.push_str(&String::from_utf8_lossy(&output.stdout));
+                buffer.push_str(&String::from_utf8_lossy(&output.stderr));
+            });
+            output.status
+        })
+    } else {
+        command.status().await
+    };
+
+    match result {
+        Ok(status) => status.success(),
+        Err(e) => {
+            task_println(&format!("[ERROR] + {}: {}", error_message, e));
+            false
+        }
+    }
+}
+
+// Helper functions for test tasks
+
+// Number of passed and failed tests reported by the test tasks
+static TEST_COUNTS: Mutex<(usize, usize)> = Mutex::new((0, 0));
+
+// Add passed and failed tests to the counts
+fn record_tests(passed: usize, failed: usize) {
+    if let Ok(mut counts) = TEST_COUNTS.lock() {
+        counts.0 += passed;
+        counts.1 += failed;
+    }
+}
+
+// Read the passed and failed counts from a `cargo test`, pytest, ctest, Jest, Vitest or
+// `node --test` summary line
+fn parse_test_summary(line: &str) -> Option<(usize, usize)> {
+    let line = line.trim();
+
+    // `node --test` reports the counts on their own lines, `# pass 3` and `# fail 1`
+    if let Some(passed) = line.strip_prefix("# pass ") {
+        return Some((passed.trim().parse().ok()?, 0));
+    }
+    if let Some(failed) = line.strip_prefix("# fail ") {
+        return Some((0, failed.trim().parse().ok()?));
+    }
+
+    // zig build ends with `Build Summary: 3/5 steps succeeded; 1 failed; 4/5 tests passed; 1 failed`
+    if let Some(summary) = line.strip_prefix("Build Summary:") {
+        let tests = summary
+            .split(';')
+            .find_map(|part| part.trim().strip_suffix(" tests passed"))?;
+        let (passed, total) = tests.split_once('/')?;
+        let passed = passed.parse::<usize>().ok()?;
+        let total = total.parse::<usize>().ok()?;
+        return Some((passed, total.saturating_sub(passed)));
+    }
+
+    // ctest ends with `75% tests passed, 1 tests failed out of 4`
+    if let Some((passed, total)) = line.split_once(" tests failed out of ") {
+        let failed = passed.split_whitespace().last()?.parse::<usize>().ok()?;
+        let total = total.trim().parse::<usize>().ok()?;
+        return Some((total.saturating_sub(failed), failed));
+    }
+
+    // Jest prints `Tests: 1 failed, 3 passed, 4 total` and Vitest `Tests  3 passed (3)`
+    let is_summary = line.starts_with("test result:")
+        || line.starts_with("Tests:")
+        || line.starts_with("Tests ")
+        || (line.starts_with('=') && line.ends_with('='));
+    if !is_summary {
+        return None;
+    }
+
+    let words: Vec<&str> = line
+        .split(|c: char| c.is_whitespace() || c == ',' || c == ';')
+        .filter(|word| !word.is_empty())
+        .collect();
+
+    let mut counts = None;
+    for pair in words.windows(2) {
+        if let Ok(count) = pair[0].parse::<usize>() {
+            let (passed, failed) = counts.get_or_insert((0, 0));
+            match pair[1].trim_end_matches('.') {
+                "passed" => *passed += count,
+                "failed" | "error" | "errors" => *failed += count,
+                _ => {}
+            }
+        }
+    }
+    counts
+}
+
+// Print the output of a test command, recording the counts of its summary lines
+async fn forward_test_output(reader: impl AsyncRead + Unpin) {
+    let mut lines = BufReader::new(reader).lines();
+    while let Ok(Some(line)) = lines.next_line().await {
+        if let Some((passed, failed)) = parse_test_summary(&line) {
+            record_tests(passed, failed);
+        }
+        task_println(&line);
+    }
+}
+
+// Run a test command line by line, counting the tests from its summary lines
+async fn run_test_command(command: &mut Command, error_message: &str) -> bool {
+    let mut child = match command
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+    {
+        Ok(child) => child,
+        Err(e) => {
+            task_println(&format!("[ERROR] + {}: {}", error_message, e));
+            return false;
+        }
+    };
+
+    if let (Some(stdout), Some(stderr)) = (child.stdout.take(), child.stderr.take()) {
+        tokio::join!(forward_test_output(stdout), forward_test_output(stderr));
+    }
+
+    child.wait().await.is_ok_and(|status| status.success())
+}
+
 // Helper functions for UV installation
 
 // Check if UV is installed
@@ -131,12 +323,54 @@ async fn execute_uv_command(args: &[&str]) -> bool {
     }
 
     // Execute the UV command
-    Command::new("uv")
-        .args(args)
-        .status()
-        .await
-        .expect("Failed to execute UV command")
-        .success()
+    run_command(
+        Command::new("uv").args(args),
+        "Failed to execute UV command",
+    )
+    .await
+}
+
+// Get the entry point a Python project runs without a script
+async fn default_python_target() -> Option<String> {
+    let pyproject = python::read_pyproject().await;
+    let entry_points = python::detect_entry_points(pyproject.as_ref()).await;
+    let target = entry_points.default_target();
+    if target.is_none() {
+        task_println(&format!(
+            "[ERROR] + No entry point to run, found: {}",
+            entry_points.names().join(", ")
+        ));
+    }
+    target
+}
+
+// Get the Python interpreter that creates virtual environments
+fn system_python() -> &'static str {
+    if cfg!(windows) { "python" } else { "python3" }
+}
+
+// Create the virtual environment of a pip project, or reuse the existing one
+async fn ensure_venv() -> bool {
+    if tokio::fs::metadata(VENV_DIR).await.is_ok() {
+        return true;
+    }
+    run_command(
+        Command::new(system_python()).args(["-m", "venv", VENV_DIR]),
+        "Failed to create the virtual environment",
+    )
+    .await
+}
+
+// Execute a command with the Python interpreter of the virtual environment
+async fn execute_venv_python(args: &[&str]) -> bool {
+    if !ensure_venv().await {
+        return false;
+    }
+    run_command(
+        Command::new(venv_program("python")).args(args),
+        "Failed to execute the Python of the virtual environment",
+    )
+    .await
 }
 
 // Concrete task factories
@@ -150,11 +384,408 @@ fn create_uv_lock_task() -> Task {
 
 // Create a UV run task
 fn create_uv_run_task() -> Task {
-    Task::new("uv_run", "uv run main.py", || async {
-        execute_uv_command(&["run", "main.py"]).await
+    Task::new("uv_run", "uv run", || async {
+        // Run the entry point of pyproject.toml, like `lox run` does
+        let Some(target) = default_python_target().await else {
+            return false;
+        };
+        let words = shlex::split(&target).unwrap_or_default();
+        let args: Vec<&str> = ["run"]
+            .into_iter()
+            .chain(words.iter().map(String::as_str))
+            .collect();
+        execute_uv_command(&args).await
+    })
+}
+
+// Create a UV sync task, with every extra and the dev dependencies
+fn create_uv_sync_dev_task() -> Task {
+    Task::new("uv_sync_dev", "uv sync --all-extras --dev", || async {
+        execute_uv_command(&["sync", "--all-extras", "--dev"]).await
+    })
+}
+
+// Create a mypy type check task
+fn create_uv_mypy_task() -> Task {
+    Task::new("uv_mypy", "uv run mypy .", || async {
+        execute_uv_command(&["run", "mypy", "."]).await
+    })
+}
+
+// Create a pyright type check task
+fn create_uv_pyright_task() -> Task {
+    Task::new("uv_pyright", "uv run pyright", || async {
+        execute_uv_command(&["run", "pyright"]).await
+    })
+}
+
+// Create a pip virtual environment task
+fn create_pip_venv_task() -> Task {
+    Task::new("pip_venv", "python -m venv .venv", ensure_venv)
+}
+
+// Create a pip install task, from the requirements files or the project itself
+fn create_pip_install_task() -> Task {
+    Task::new("pip_install", "pip install", || async {
+        let files = crate::projects::pip::requirement_files().await;
+        let mut args = vec!["-m", "pip", "install"];
+        if files.is_empty() {
+            args.extend(["-e", "."]);
+        } else {
+            for file in &files {
+                args.extend(["-r", file.as_str()]);
+            }
+        }
+        execute_venv_python(&args).await
+    })
+}
+
+// Create a pip task installing `build`, which `python -m build` needs in the environment
+fn create_pip_install_build_task() -> Task {
+    Task::new("pip_install_build", "pip install build", || async {
+        execute_venv_python(&["-m", "pip", "install", "build"]).await
     })
 }
 
+// Create a pip build task
+fn create_pip_build_task() -> Task {
+    Task::new("pip_build", "python -m build", || async {
+        execute_venv_python(&["-m", "build"]).await
+    })
+}
+
+// Create a pip run task, for the entry point in the virtual environment
+fn create_pip_run_task() -> Task {
+    Task::new("pip_run", "python", || async {
+        let Some(target) = default_python_target().await else {
+            return false;
+        };
+        let command = crate::projects::python::run_command("pip", &target);
+        let words = shlex::split(&command).unwrap_or_default();
+        let Some((program, args)) = words.split_first() else {
+            return false;
+        };
+        run_command(
+            Command::new(program).args(args),
+            "Failed to run the project",
+        )
+        .await
+    })
+}
+
+// Execute a command of Poetry or PDM, which manage the environment themselves
+async fn execute_python_tool(tool: &str, args: &[&str]) -> bool {
+    run_command(
+        Command::new(tool).args(args),
+        &format!("Failed to execute {} command", tool),
+    )
+    .await
+}
+
+// Run the entry point of a Poetry or PDM project in its environment
+async fn run_python_tool_entry(tool: &str) -> bool {
+    let Some(target) = default_python_target().await else {
+        return false;
+    };
+    let words = shlex::split(&target).unwrap_or_default();
+    let args: Vec<&str> = ["run"]
+        .into_iter()
+        .chain(words.iter().map(String::as_str))
+        .collect();
+    execute_python_tool(tool, &args).await
+}
+
+// Create a Poetry install task
+fn create_poetry_install_task() -> Task {
+    Task::new("poetry_install", "poetry install", || async {
+        execute_python_tool("poetry", &["install"]).await
+    })
+}
+
+// Create a Poetry build task
+fn create_poetry_build_task() -> Task {
+    Task::new("poetry_build", "poetry build", || async {
+        execute_python_tool("poetry", &["build"]).await
+    })
+}
+
+// Create a Poetry run task
+fn create_poetry_run_task() -> Task {
+    Task::new("poetry_run", "poetry run", || async {
+        run_python_tool_entry("poetry").await
+    })
+}
+
+// Create a PDM install task
+fn create_pdm_install_task() -> Task {
+    Task::new("pdm_install", "pdm install", || async {
+        execute_python_tool("pdm", &["install"]).await
+    })
+}
+
+// Create a PDM build task
+fn create_pdm_build_task() -> Task {
+    Task::new("pdm_build", "pdm build", || async {
+        execute_python_tool("pdm", &["build"]).await
+    })
+}
+
+// Create a PDM run task
+fn create_pdm_run_task() -> Task {
+    Task::new("pdm_run", "pdm run", || async {
+        run_python_tool_entry("pdm").await
+    })
+}
+
+// Run a command of the project inside its conda or pixi environment
+async fn execute_in_environment(release: bool) -> bool {
+    let Some(project_type) = conda::detect_environment_type().await else {
+        task_println("[ERROR] + No `environment.yml` or `pixi.toml` found.");
+        return false;
+    };
+    let pyproject = python::read_pyproject().await;
+    let (build_commands, _) = conda::environment_commands(project_type, pyproject.as_ref()).await;
+    let command = if release {
+        build_commands.release
+    } else {
+        build_commands.dev
+    };
+
+    // Python projects have nothing to build in dev mode, the environment is enough
+    if command == "unknown" && !release {
+        return true;
+    }
+    let words = shlex::split(&command).unwrap_or_default();
+    match words.split_first() {
+        Some((program, args)) if command != "unknown" => {
+            run_command(
+                Command::new(program).args(args),
+                "Failed to build in the environment",
+            )
+            .await
+        }
+        _ => {
+            task_println("[ERROR] + No build command found for the environment.");
+            false
+        }
+    }
+}
+
+// Check whether a named conda environment exists
+async fn conda_env_exists(name: &str) -> bool {
+    let Ok(output) = Command::new("conda")
+        .args(["env", "list", "--json"])
+        .output()
+        .await
+    else {
+        return false;
+    };
+    serde_json::from_slice::<serde_json::Value>(&output.stdout)
+        .ok()
+        .and_then(|list| list.get("envs").and_then(|envs| envs.as_array()).cloned())
+        .unwrap_or_default()
+        .iter()
+        .filter_map(|env| env.as_str())
+        .any(|path| Path::new(path).file_name().is_some_and(|file| file == name))
+}
+
+// Create a conda environment task, which reuses the environment when it exists
+fn create_conda_env_task() -> Task {
+    Task::new("conda_env", "conda env create", || async {
+        let Some(file) = conda::find_environment_file().await else {
+            task_println("[ERROR] + No `environment.yml` found.");
+            return false;
+        };
+        if let Some(name) = conda::environment_name().await
+            && conda_env_exists(&name).await
+        {
+            return true;
+        }
+        run_command(
+            Command::new("conda").args(["env", "create", "-f", file]),
+            "Failed to execute conda env create",
+        )
+        .await
+    })
+}
+
+// Create a conda environment update task, which creates the environment when it is missing
+fn create_conda_update_task() -> Task {
+    Task::new("conda_update", "conda env update --prune", || async {
+        let Some(file) = conda::find_environment_file().await else {
+            task_println("[ERROR] + No `environment.yml` found.");
+            return false;
+        };
+        run_command(
+            Command::new("conda").args(["env", "update", "-f", file, "--prune"]),
+            "Failed to execute conda env update",
+        )
+        .await
+    })
+}
+
+// Create a pixi install task
+fn create_pixi_install_task() -> Task {
+    Task::new("pixi_install", "pixi install", || async {
+        run_command(
+            Command::new("pixi").arg("install"),
+            "Failed to execute pixi install",
+        )
+        .await
+    })
+}
+
+// Create an environment build task, in dev mode
+fn create_env_build_task() -> Task {
+    Task::new("env_build", "build in the environment", || async {
+        execute_in_environment(false).await
+    })
+}
+
+// Create an environment build task, in release mode
+fn create_env_build_release_task() -> Task {
+    Task::new(
+        "env_build_release",
+        "build in the environment --release",
+        || async { execute_in_environment(true).await },
+    )
+}
+
+// CMake project tasks
+
+// Configure the build directory of a profile, asking the File API for the targets
+fn create_cmake_configure_task(id: &str, release: bool) -> Task {
+    let args = cmake::configure_args(release);
+    Task::new(id, &format!("cmake {}", args.join(" ")), move || {
+        let args = args.clone();
+        async move {
+            if let Err(e) = cmake::write_query(cmake::build_dir(release)).await {
+                task_println(&format!(
+                    "[ERROR] + Failed to query the CMake File API: {}",
+                    e
+                ));
+                return false;
+            }
+            run_command(Command::new("cmake").args(&args), "Failed to execute cmake").await
+        }
+    })
+}
+
+// Build a configured profile
+fn create_cmake_build_task(id: &str, release: bool) -> Task {
+    let args = cmake::build_args(release);
+    Task::new(id, &format!("cmake {}", args.join(" ")), move || {
+        let args = args.clone();
+        async move {
+            run_command(
+                Command::new("cmake").args(&args),
+                "Failed to execute cmake --build",
+            )
+            .await
+        }
+    })
+}
+
+// Create a ctest task, for the tests of the dev build
+fn create_cmake_test_task() -> Task {
+    let dir = cmake::build_dir(false);
+    Task::new(
+        "cmake_test",
+        &format!("ctest --test-dir {} --output-on-failure", dir),
+        move || async move {
+            run_test_command(
+                Command::new("ctest").args(["--test-dir", dir, "--output-on-failure"]),
+                "Failed to execute ctest",
+            )
+            .await
+        },
+    )
+}
+
+// Go project tasks
+
+// Build every main package of the module into the directory of a profile
+fn create_go_build_task(id: &str, release: bool) -> Task {
+    let args = go::build_args(release);
+    Task::new(id, &go::build_command(release), move || {
+        let args = args.clone();
+        async move { run_command(Command::new("go").args(&args), "Failed to execute go build").await }
+    })
+}
+
+// Create a go test task, for every package of the module
+fn create_go_test_task() -> Task {
+    Task::new("go_test", "go test ./...", || async {
+        run_test_command(
+            Command::new("go").args(["test", "./..."]),
+            "Failed to execute go test",
+        )
+        .await
+    })
+}
+
+// Zig project tasks
+
+// Build the project and install it to `zig-out`, with the optimize mode of a profile
+fn create_zig_build_task(id: &str, args: &'static [&'static str]) -> Task {
+    Task::new(id, &format!("zig {}", args.join(" ")), move || async move {
+        run_command(
+            Command::new("zig").args(args),
+            "Failed to execute zig build",
+        )
+        .await
+    })
+}
+
+// Create a zig test task, running the `test` step of build.zig
+fn create_zig_test_task() -> Task {
+    Task::new("zig_test", "zig build test", || async {
+        run_test_command(
+            Command::new("zig").args(["build", "test"]),
+            "Failed to execute zig build test",
+        )
+        .await
+    })
+}
+
+// Node.js project tasks
+
+// Create a task running a command of a Node.js package, found in package.json
+fn create_node_task(id: &str, command: Option<String>, is_test: bool) -> Task {
+    let name = command.clone().unwrap_or_else(|| String::from("unknown"));
+    Task::new(id, &name, move || {
+        let command = command.clone();
+        async move {
+            let words = command
+                .as_deref()
+                .and_then(shlex::split)
+                .unwrap_or_default();
+            let Some((program, args)) = words.split_first() else {
+                task_println("[ERROR] + No command found in package.json.");
+                return false;
+            };
+            let mut command = Command::new(program);
+            command.args(args);
+            let error_message = format!("Failed to execute {}", program);
+            if is_test {
+                run_test_command(&mut command, &error_message).await
+            } else {
+                run_command(&mut command, &error_message).await
+            }
+        }
+    })
+}
+
+// Create the tasks of a Node.js package, with the package manager of its lockfile
+fn create_node_tasks(commands: &node::NodeCommands) -> Vec<Task> {
+    vec![
+        create_node_task("node_install", Some(commands.install.clone()), false),
+        create_node_task("node_dev", commands.dev.clone(), false),
+        create_node_task("node_build", commands.build.clone(), false),
+        create_node_task("node_test", Some(commands.test.clone()), true),
+    ]
+}
+
 // Create a UV build task
 fn create_uv_build_task() -> Task {
     Task::new("uv_build", "uv build", || async {
@@ -173,14 +804,14 @@ fn create_uv_ruff_check_task() -> Task {
             println!("[TIP] + `uv` already installed, please restart the terminal.");
             return false;
         }
-        Command::new("uvx")
-            .arg("ruff")
-            .arg("check")
-            .status()
-            .await
-            .expect("Failed to execute uvx ruff check")
-            .success()
+        run_command(
+            Command::new("uvx").arg("ruff").arg("check"),
+            "Failed to execute uvx ruff check",
+        )
+        .await
     })
+    // Check the files as formatted, a failing check still leaves them formatted
+    .with_dependencies(&[super::UV_RUFF_FORMAT.to_string()])
 }
 
 // Create a UV ruff format task
@@ -194,76 +825,163 @@ fn create_uv_ruff_format_task() -> Task {
             println!("[TIP] + `uv` already installed, please restart the terminal.");
             return false;
         }
-        Command::new("uvx")
-            .arg("ruff")
-            .arg("format")
-            .status()
-            .await
-            .expect("Failed to execute uvx ruff format")
-            .success()
+        run_command(
+            Command::new("uvx").arg("ruff").arg("format"),
+            "Failed to execute uvx ruff format",
+        )
+        .await
+    })
+}
+
+// Create a UV pytest task
+fn create_uv_pytest_task() -> Task {
+    Task::new("uv_pytest", "uv run pytest", || async {
+        // Check if UV is installed first, like the other UV tasks
+        if !is_uv_installed().await {
+            if !install_uv().await {
+                return false;
+            }
+            println!("[TIP] + `uv` already installed, please restart the terminal.");
+            return false;
+        }
+
+        run_test_command(
+            Command::new("uv").arg("run").arg("pytest"),
+            "Failed to execute uv run pytest",
+        )
+        .await
     })
 }
 
 // Cargo project tasks
 
+// Package of the workspace picked with `-p`, for the cargo tasks that take one
+static CARGO_PACKAGE: OnceLock<String> = OnceLock::new();
+
+// Get the arguments of a cargo command, limited to the picked package
+fn cargo_args(args: &[&str]) -> Vec<String> {
+    let mut args: Vec<String> = args.iter().map(|arg| arg.to_string()).collect();
+    if let Some(package) = CARGO_PACKAGE.get() {
+        args.push("-p".to_string());
+        args.push(package.clone());
+    }
+    args
+}
+
 // Create a cargo update task
 fn create_cargo_update_task() -> Task {
     Task::new("cargo_update", "cargo update", || async {
-        Command::new("cargo")
-            .arg("update")
-            .status()
-            .await
-            .expect("Failed to execute cargo update")
-            .success()
+        run_command(
+            Command::new("cargo").arg("update"),
+            "Failed to execute cargo update",
+        )
+        .await
     })
 }
 
 // Create a cargo fmt task
 fn create_cargo_fmt_task() -> Task {
-    Task::new("cargo_fmt", "cargo fmt", || async {
-        Command::new("cargo")
-            .arg("fmt")
-            .status()
-            .await
-            .expect("Failed to execute cargo fmt")
-            .success()
-    })
+    let args = cargo_args(&["fmt"]);
+    Task::new(
+        "cargo_fmt",
+        &format!("cargo {}", args.join(" ")),
+        move || {
+            let args = args.clone();
+            async move {
+                run_command(
+                    Command::new("cargo").args(&args),
+                    "Failed to execute cargo fmt",
+                )
+                .await
+            }
+        },
+    )
 }
 
 // Create a cargo check task
 fn create_cargo_check_task() -> Task {
-    Task::new("cargo_check", "cargo check", || async {
-        Command::new("cargo")
-            .arg("check")
-            .status()
-            .await
-            .expect("Failed to execute cargo check")
-            .success()
-    })
+    let args = cargo_args(&["check"]);
+    Task::new(
+        "cargo_check",
+        &format!("cargo {}", args.join(" ")),
+        move || {
+            let args = args.clone();
+            async move {
+                run_command(
+                    Command::new("cargo").args(&args),
+                    "Failed to execute cargo check",
+                )
+                .await
+            }
+        },
+    )
 }
 
 // Create a cargo build task
 fn create_cargo_build_task() -> Task {
-    Task::new("cargo_build", "cargo build", || async {
-        Command::new("cargo")
-            .arg("build")
-            .status()
-            .await
-            .expect("Failed to execute cargo build")
-            .success()
-    })
+    let args = cargo_args(&["build"]);
+    Task::new(
+        "cargo_build",
+        &format!("cargo {}", args.join(" ")),
+        move || {
+            let args = args.clone();
+            async move {
+                run_command(
+                    Command::new("cargo").args(&args),
+                    "Failed to execute cargo build",
+                )
+                .await
+            }
+        },
+    )
 }
 
 // Create a cargo build release task
 fn create_cargo_build_release_task() -> Task {
-    Task::new("cargo_build_release", "cargo build --release", || async {
-        Command::new("cargo")
-            .arg("build")
-            .arg("--release")
-            .status()
-            .await
-            .expect("Failed to execute cargo build --release")
-            .success()
+    let args = cargo_args(&["build", "--release"]);
+    Task::new(
+        "cargo_build_release",
+        &format!("cargo {}", args.join(" ")),
+        move || {
+            let args = args.clone();
+            async move {
+                run_command(
+                    Command::new("cargo").args(&args),
+                    "Failed to execute cargo build --release",
+                )
+                .await
+            }
+        },
+    )
+}
+
+// Create a cargo test task
+fn create_cargo_test_task() -> Task {
+    let args = cargo_args(&["test"]);
+    Task::new(
+        "cargo_test",
+        &format!("cargo {}", args.join(" ")),
+        move || {
+            let args = args.clone();
+            async move {
+                run_test_command(
+                    Command::new("cargo").args(&args),
+                    "Failed to execute cargo test",
+                )
+                .await
+            }
+        },
+    )
+}
+
+// Create an FPM build task
+fn create_fpm_build_task() -> Task {
+    Task::new("fpm_build", "fpm build", || async {
+        run_command(
+            Command::new("fpm").arg("build"),
+            "Failed to execute fpm build",
+        )
+        .await
     })
 }
 
@@ -273,57 +991,282 @@ fn create_fpm_build_release_task() -> Task {
         "fpm_build_release",
         "fpm build --profile release",
         || async {
-            Command::new("fpm")
-                .arg("build")
-                .arg("--profile")
-                .arg("release")
-                .status()
-                .await
-                .expect("Failed to execute fpm build --profile release")
-                .success()
+            run_command(
+                Command::new("fpm")
+                    .arg("build")
+                    .arg("--profile")
+                    .arg("release"),
+                "Failed to execute fpm build --profile release",
+            )
+            .await
         },
     )
 }
 
+// Create an FPM test task
+fn create_fpm_test_task() -> Task {
+    Task::new("fpm_test", "fpm test", || async {
+        run_test_command(
+            Command::new("fpm").arg("test"),
+            "Failed to execute fpm test",
+        )
+        .await
+    })
+}
+
+// User-defined tasks
+
+// Tasks from the `[tasks]` table of lox.toml, registered once the project is loaded
+static USER_TASKS: OnceLock<Vec<UserTask>> = OnceLock::new();
+
+// Set once the project is known to be a Node.js package, with the commands of its node tasks
+static NODE_COMMANDS: OnceLock<node::NodeCommands> = OnceLock::new();
+
+// Create a task from a `[tasks.<name>]` table
+fn create_user_task(user_task: &UserTask) -> Task {
+    let words = std::iter::once(&user_task.cmd).chain(&user_task.args);
+    let display_name = shlex::try_join(words.clone().map(String::as_str))
+        .unwrap_or_else(|_| words.cloned().collect::<Vec<_>>().join(" "));
+
+    let user_task_clone = user_task.clone();
+    Task::new(&user_task.name, &display_name, move || {
+        let user_task = user_task_clone.clone();
+        async move {
+            let mut command = Command::new(&user_task.cmd);
+            command.args(&user_task.args).envs(&user_task.env);
+            if let Some(cwd) = &user_task.cwd {
+                command.current_dir(cwd);
+            }
+
+            run_command(
+                &mut command,
+                &format!("Failed to execute {}", user_task.cmd),
+            )
+            .await
+        }
+    })
+    .with_description(user_task.description.clone())
+    .with_dependencies(&user_task.depends_on)
+}
+
 // Task registry to store and retrieve tasks by ID
 struct TaskRegistry {
-    tasks: Vec<Box<Task>>,
+    tasks: Vec<Task>,
 }
 
 impl TaskRegistry {
     fn new() -> Self {
-        let tasks: Vec<Box<Task>> = vec![
-            Box::new(create_uv_lock_task()),
-            Box::new(create_uv_run_task()),
-            Box::new(create_uv_build_task()),
-            Box::new(create_uv_ruff_check_task()),
-            Box::new(create_uv_ruff_format_task()),
-            Box::new(create_cargo_update_task()),
-            Box::new(create_cargo_fmt_task()),
-            Box::new(create_cargo_check_task()),
-            Box::new(create_cargo_build_task()),
-            Box::new(create_cargo_build_release_task()),
-            Box::new(create_flang_build_dev_task()),
-            Box::new(create_flang_build_release_task()),
-            Box::new(create_fpm_build_release_task()),
+        let mut tasks: Vec<Task> = vec![
+            create_uv_lock_task(),
+            create_uv_run_task(),
+            create_uv_build_task(),
+            create_uv_ruff_check_task(),
+            create_uv_ruff_format_task(),
+            create_cargo_update_task(),
+            create_cargo_fmt_task(),
+            create_cargo_check_task(),
+            create_cargo_build_task(),
+            create_cargo_build_release_task(),
+            create_flang_build_dev_task(),
+            create_flang_build_release_task(),
+            create_fpm_build_task(),
+            create_fpm_build_release_task(),
+            create_cargo_test_task(),
+            create_uv_pytest_task(),
+            create_uv_sync_dev_task(),
+            create_uv_mypy_task(),
+            create_uv_pyright_task(),
+            create_pip_venv_task(),
+            create_pip_install_task(),
+            create_pip_install_build_task(),
+            create_pip_build_task(),
+            create_pip_run_task(),
+            create_poetry_install_task(),
+            create_poetry_build_task(),
+            create_poetry_run_task(),
+            create_pdm_install_task(),
+            create_pdm_build_task(),
+            create_pdm_run_task(),
+            create_conda_env_task(),
+            create_conda_update_task(),
+            create_pixi_install_task(),
+            create_env_build_task(),
+            create_env_build_release_task(),
+            create_cmake_configure_task("cmake_configure", false),
+            create_cmake_configure_task("cmake_configure_release", true),
+            create_cmake_build_task("cmake_build", false),
+            create_cmake_build_task("cmake_build_release", true),
+            create_cmake_test_task(),
+            create_go_build_task("go_build", false),
+            create_go_build_task("go_build_release", true),
+            create_go_test_task(),
+            create_zig_build_task("zig_build", &zig::DEV_ARGS),
+            create_zig_build_task("zig_build_release", &zig::RELEASE_ARGS),
+            create_zig_test_task(),
+            create_fpm_test_task(),
+            create_flang_test_task(),
         ];
+        // Only Node.js projects read their package.json
+        if let Some(commands) = NODE_COMMANDS.get() {
+            tasks.extend(create_node_tasks(commands));
+        }
+
+        let mut registry = Self { tasks };
+        for user_task in USER_TASKS.get().into_iter().flatten() {
+            registry.register(create_user_task(user_task));
+        }
+        registry
+    }
 
-        Self { tasks }
+    // Register a task, replacing any task with the same ID
+    fn register(&mut self, task: Task) {
+        self.tasks.retain(|existing| existing.id() != task.id());
+        self.tasks.push(task);
     }
 
-    fn get_task_by_id(&self, id: &str) -> Option<&Box<Task>> {
+    fn get_task_by_id(&self, id: &str) -> Option<&Task> {
         self.tasks.iter().find(|task| task.id() == id)
     }
 
-    async fn execute_task_by_id(&self, id: &str) -> bool {
-        if let Some(task) = self.get_task_by_id(id) {
-            println!("  - Task | {} | ", task.name());
-            let success = task.execute().await;
-            println!("{}", task.get_result_message(success));
-            success
+    // Collect the tasks and everything they depend on, dependencies first
+    fn resolve(&self, ids: &[String]) -> Result<Vec<&Task>, String> {
+        let mut order = Vec::new();
+        let mut visiting = Vec::new();
+        for id in ids {
+            self.visit(id, &mut visiting, &mut order)?;
+        }
+        Ok(order)
+    }
+
+    // Depth-first visit of the dependency graph, failing on cycles
+    fn visit<'a>(
+        &'a self,
+        id: &str,
+        visiting: &mut Vec<String>,
+        order: &mut Vec<&'a Task>,
+    ) -> Result<(), String> {
+        if order.iter().any(|task| task.id() == id) {
+            return Ok(());
+        }
+        if visiting.iter().any(|visited| visited == id) {
+            return Err(format!(
+                "Task dependency cycle: {} -> {}",
+                visiting.join(" -> "),
+                id
+            ));
+        }
+
+        let task = self
+            .get_task_by_id(id)
+            .ok_or_else(|| format!("Task with ID '{}' not found", id))?;
+
+        visiting.push(id.to_string());
+        for dependency in &task.depends_on {
+            self.visit(dependency, visiting, order)?;
+        }
+        visiting.pop();
+
+        order.push(task);
+        Ok(())
+    }
+
+    // Run tasks as a dependency graph, starting independent ones concurrently
+    //
+    // Returns the names of the tasks that failed.
+    async fn execute_tasks(&self, ids: &[String]) -> Result<(), Vec<String>> {
+        let mut pending = match self.resolve(ids) {
+            Ok(order) => order,
+            Err(e) => {
+                eprintln!("Error: {}", e);
+                return Err(ids.to_vec());
+            }
+        };
+        let mut failed_names = Vec::new();
+
+        let mut done: HashSet<String> = HashSet::new();
+        let mut failed: HashSet<String> = HashSet::new();
+        let mut running = JoinSet::new();
+
+        loop {
+            // Skip the tasks whose dependencies failed
+            let mut index = 0;
+            while index < pending.len() {
+                let task = pending[index];
+                if task.depends_on.iter().any(|id| failed.contains(id)) {
+                    println!("  - Task | {} | Skipped.", task.name());
+                    failed.insert(task.id().to_string());
+                    pending.remove(index);
+                } else {
+                    index += 1;
+                }
+            }
+
+            // Start the tasks whose dependencies are done, up to the job limit
+            let ready: Vec<&Task> = pending
+                .iter()
+                .copied()
+                .filter(|task| task.depends_on.iter().all(|id| done.contains(id)))
+                .collect();
+
+            // A task that can't overlap with another one keeps its output live
+            let live = running.is_empty() && (ready.len() == 1 || jobs() == 1);
+
+            for task in ready {
+                if running.len() >= jobs() {
+                    break;
+                }
+                pending.retain(|pending_task| pending_task.id() != task.id());
+
+                let id = task.id().to_string();
+                let execute = task.execute();
+
+                if live {
+                    println!("  - Task | {} | ", task.name());
+                    running.spawn(async move { (id, execute.await, None) });
+                } else {
+                    running.spawn(async move {
+                        let (success, output) = TASK_OUTPUT
+                            .scope(RefCell::new(String::new()), async move {
+                                let success = execute.await;
+                                (success, TASK_OUTPUT.with(|output| output.take()))
+                            })
+                            .await;
+                        (id, success, Some(output))
+                    });
+                }
+            }
+
+            let Some(result) = running.join_next().await else {
+                break;
+            };
+            let (id, success, output) = match result {
+                Ok(result) => result,
+                Err(e) => std::panic::resume_unwind(e.into_panic()),
+            };
+
+            // Buffered tasks print their whole block at once
+            if let Some(task) = self.get_task_by_id(&id) {
+                if let Some(output) = output {
+                    println!("  - Task | {} | ", task.name());
+                    print!("{}", output);
+                }
+                println!("{}", task.get_result_message(success));
+                if !success {
+                    failed_names.push(task.name().to_string());
+                }
+            }
+
+            if success {
+                done.insert(id);
+            } else {
+                failed.insert(id);
+            }
+        }
+
+        if failed_names.is_empty() {
+            Ok(())
         } else {
-            eprintln!("Error: Task with ID '{}' not found", id);
-            false
+            Err(failed_names)
         }
     }
 }
@@ -346,21 +1289,22 @@ fn create_flang_build_dev_task() -> Task {
 
         // Build each file in order
         for file in order {
-            println!("    - Building: {}", file);
-            let status = tokio::process::Command::new("flang")
-                .arg("-g")
-                .arg("-c")
-                .arg(&file)
-                .arg("-o")
-                .arg(format!(
-                    "./target/dev/{}.o",
-                    file.replace(".f90", "").replace(".f", "")
-                ))
-                .status()
-                .await
-                .expect("Failed to execute flang command");
+            task_println(&format!("    - Building: {}", file));
+            let compiled = run_command(
+                Command::new("flang")
+                    .arg("-g")
+                    .arg("-c")
+                    .arg(&file)
+                    .arg("-o")
+                    .arg(format!(
+                        "./target/dev/{}.o",
+                        file.replace(".f90", "").replace(".f", "")
+                    )),
+                "Failed to execute flang command",
+            )
+            .await;
 
-            if !status.success() {
+            if !compiled {
                 return false;
             }
         }
@@ -369,14 +1313,13 @@ fn create_flang_build_dev_task() -> Task {
         let object_files: Vec<String> = {
             let mut files = Vec::new();
             if let Ok(mut entries) = tokio::fs::read_dir("./target/dev").await {
-                while let Some(entry_result) = entries.next_entry().await.ok() {
-                    if let Some(entry) = entry_result {
-                        let path = entry.path();
-                        if path.is_file() && path.extension().unwrap_or_default() == "o" {
-                            if let Some(path_str) = path.to_str() {
-                                files.push(path_str.to_string());
-                            }
-                        }
+                while let Ok(Some(entry)) = entries.next_entry().await {
+                    let path = entry.path();
+                    if path.is_file()
+                        && path.extension().unwrap_or_default() == "o"
+                        && let Some(path_str) = path.to_str()
+                    {
+                        files.push(path_str.to_string());
                     }
                 }
             }
@@ -390,16 +1333,15 @@ fn create_flang_build_dev_task() -> Task {
                 .unwrap();
             let main_name = main_file.file_stem().unwrap().to_str().unwrap();
 
-            let status = tokio::process::Command::new("flang")
-                .arg("-g")
-                .args(object_files)
-                .arg("-o")
-                .arg(format!("./target/dev/{}.out", main_name))
-                .status()
-                .await
-                .expect("Failed to link Fortran executable");
-
-            status.success()
+            run_command(
+                Command::new("flang")
+                    .arg("-g")
+                    .args(object_files)
+                    .arg("-o")
+                    .arg(format!("./target/dev/{}.out", main_name)),
+                "Failed to link Fortran executable",
+            )
+            .await
         } else {
             true
         }
@@ -418,21 +1360,22 @@ fn create_flang_build_release_task() -> Task {
 
         // Build each file in order
         for file in order {
-            println!("    - Building: {}", file);
-            let status = tokio::process::Command::new("flang")
-                .arg("-O3")
-                .arg("-c")
-                .arg(&file)
-                .arg("-o")
-                .arg(format!(
-                    "./target/release/{}.o",
-                    file.replace(".f90", "").replace(".f", "")
-                ))
-                .status()
-                .await
-                .expect("Failed to execute flang command");
+            task_println(&format!("    - Building: {}", file));
+            let compiled = run_command(
+                Command::new("flang")
+                    .arg("-O3")
+                    .arg("-c")
+                    .arg(&file)
+                    .arg("-o")
+                    .arg(format!(
+                        "./target/release/{}.o",
+                        file.replace(".f90", "").replace(".f", "")
+                    )),
+                "Failed to execute flang command",
+            )
+            .await;
 
-            if !status.success() {
+            if !compiled {
                 return false;
             }
         }
@@ -441,14 +1384,13 @@ fn create_flang_build_release_task() -> Task {
         let object_files: Vec<String> = {
             let mut files = Vec::new();
             if let Ok(mut entries) = tokio::fs::read_dir("./target/release").await {
-                while let Some(entry_result) = entries.next_entry().await.ok() {
-                    if let Some(entry) = entry_result {
-                        let path = entry.path();
-                        if path.is_file() && path.extension().unwrap_or_default() == "o" {
-                            if let Some(path_str) = path.to_str() {
-                                files.push(path_str.to_string());
-                            }
-                        }
+                while let Ok(Some(entry)) = entries.next_entry().await {
+                    let path = entry.path();
+                    if path.is_file()
+                        && path.extension().unwrap_or_default() == "o"
+                        && let Some(path_str) = path.to_str()
+                    {
+                        files.push(path_str.to_string());
                     }
                 }
             }
@@ -462,24 +1404,250 @@ fn create_flang_build_release_task() -> Task {
                 .unwrap();
             let main_name = main_file.file_stem().unwrap().to_str().unwrap();
 
-            let status = tokio::process::Command::new("flang")
-                .arg("-O3")
-                .args(object_files)
-                .arg("-o")
-                .arg(format!("./target/release/{}.out", main_name))
-                .status()
-                .await
-                .expect("Failed to link Fortran executable");
-
-            status.success()
+            run_command(
+                Command::new("flang")
+                    .arg("-O3")
+                    .args(object_files)
+                    .arg("-o")
+                    .arg(format!("./target/release/{}.out", main_name)),
+                "Failed to link Fortran executable",
+            )
+            .await
         } else {
             true
         }
     })
 }
 
+// Create a Fortran test task, building and running every program in `test`
+fn create_flang_test_task() -> Task {
+    Task::new(super::FLANG_TEST, "flang test", || async {
+        let order = crate::projects::flang::get_compilation_order().await;
+        let main_file = crate::projects::flang::find_main_program_file().await;
+
+        // Create target directory
+        let _ = tokio::fs::create_dir_all("./target/test").await;
+
+        // Build the project files, except the main program, for the tests to link with
+        let mut object_files = Vec::new();
+        for file in order {
+            if main_file
+                .as_ref()
+                .is_some_and(|main_file| main_file.file_name().unwrap_or_default() == file.as_str())
+            {
+                continue;
+            }
+
+            task_println(&format!("    - Building: {}", file));
+            let object_file = format!(
+                "./target/test/{}.o",
+                file.replace(".f90", "").replace(".f", "")
+            );
+            let compiled = run_command(
+                Command::new("flang")
+                    .arg("-g")
+                    .arg("-c")
+                    .arg(&file)
+                    .arg("-o")
+                    .arg(&object_file),
+                "Failed to execute flang command",
+            )
+            .await;
+
+            if !compiled {
+                return false;
+            }
+            object_files.push(object_file);
+        }
+
+        let test_files = crate::projects::flang::find_test_program_files().await;
+        if test_files.is_empty() {
+            task_println("    - No test programs found in `test`.");
+            return true;
+        }
+
+        // Build and run each test program, a test passes when it exits with 0
+        let mut all_passed = true;
+        for test_file in test_files {
+            let test_name = test_file.file_stem().unwrap().to_str().unwrap();
+            let executable = format!("./target/test/{}.out", test_name);
+
+            let built = run_command(
+                Command::new("flang")
+                    .arg("-g")
+                    .arg(&test_file)
+                    .args(&object_files)
+                    .arg("-o")
+                    .arg(&executable),
+                "Failed to build Fortran test",
+            )
+            .await;
+            let passed = built
+                && run_command(&mut Command::new(&executable), "Failed to run Fortran test").await;
+
+            task_println(&format!(
+                "    - Testing: {} | {}.",
+                test_name,
+                if passed { "Passed" } else { "Failed" }
+            ));
+            record_tests(usize::from(passed), usize::from(!passed));
+            all_passed &= passed;
+        }
+
+        all_passed
+    })
+}
+
 // Public API for the task system
-pub async fn execute_task_by_id(task_id: &str) -> bool {
+
+// Run tasks and their dependencies, independent ones in parallel
+pub async fn execute_tasks(task_ids: &[String]) -> Result<(), Vec<String>> {
     let registry = TaskRegistry::new();
-    registry.execute_task_by_id(task_id).await
+    registry.execute_tasks(task_ids).await
+}
+
+// Run a command line from lox.toml as a one-off task
+pub async fn execute_command(id: &str, command: &str) -> Result<(), Vec<String>> {
+    let words = shlex::split(command).unwrap_or_default();
+    let Some((cmd, args)) = words.split_first() else {
+        eprintln!("Error: Empty or malformed command: {}", command);
+        return Err(vec![command.to_string()]);
+    };
+
+    let user_task = UserTask {
+        name: id.to_string(),
+        cmd: cmd.to_string(),
+        args: args.to_vec(),
+        cwd: None,
+        env: Default::default(),
+        description: None,
+        depends_on: Vec::new(),
+    };
+
+    let mut registry = TaskRegistry::new();
+    registry.register(create_user_task(&user_task));
+    registry.execute_tasks(&[user_task.name]).await
+}
+
+// Limit the number of tasks running at the same time
+pub fn set_jobs(jobs: usize) {
+    let _ = JOBS.set(jobs.max(1));
+}
+
+// Limit the cargo tasks to one package of the workspace
+pub fn set_cargo_package(package: &str) {
+    let _ = CARGO_PACKAGE.set(package.to_string());
+}
+
+// Register the tasks of a Node.js package, from its package.json
+pub async fn register_node_tasks() {
+    let _ = NODE_COMMANDS.set(node::node_commands().await);
+}
+
+// Register the tasks defined in lox.toml next to the built-in ones
+pub fn register_user_tasks(user_tasks: &[UserTask]) {
+    let _ = USER_TASKS.set(user_tasks.to_vec());
+}
+
+// List the ID and description of every registered task
+pub fn list_tasks() -> Vec<(String, String)> {
+    TaskRegistry::new()
+        .tasks
+        .iter()
+        .map(|task| (task.id().to_string(), task.description().to_string()))
+        .collect()
+}
+
+// Get the number of passed and failed tests reported so far
+pub fn test_counts() -> (usize, usize) {
+    TEST_COUNTS.lock().map(|counts| *counts).unwrap_or((0, 0))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::parse_test_summary;
+
+    // Count every summary line of an output, like `run_test_command` does
+    fn count(output: &str) -> Option<(usize, usize)> {
+        output.lines().filter_map(parse_test_summary).reduce(
+            |(passed, failed), (more_passed, more_failed)| {
+                (passed + more_passed, failed + more_failed)
+            },
+        )
+    }
+
+    #[test]
+    fn cargo_test_output_adds_up_every_test_binary() {
+        let output = "\
+running 3 tests
+test config::tests::parse ... ok
+test config::tests::unknown_keys ... FAILED
+test make::tests::phony ... ok
+
+test result: FAILED. 2 passed; 1 failed; 0 ignored; 0 measured; 0 filtered out; finished in 0.01s
+
+running 1 test
+test src/lib.rs - add (line 3) ... ok
+
+test result: ok. 1 passed; 0 failed; 0 ignored; 0 measured; 0 filtered out; finished in 0.20s
+";
+        assert_eq!(count(output), Some((3, 1)));
+    }
+
+    #[test]
+    fn pytest_output_counts_the_final_line_only() {
+        let output = "\
+============================= test session starts ==============================
+platform linux -- Python 3.12.3, pytest-8.3.2, pluggy-1.5.0
+collected 7 items
+
+tests/test_app.py ..F.s.E                                                [100%]
+
+=========================== short test summary info ============================
+FAILED tests/test_app.py::test_parse - AssertionError: assert 2 == 3
+ERROR tests/test_app.py::test_db - ConnectionError
+======== 1 failed, 4 passed, 1 skipped, 2 warnings, 1 error in 0.35s =========
+";
+        assert_eq!(count(output), Some((4, 2)));
+        assert_eq!(
+            parse_test_summary(
+                "============================== 12 passed in 1.02s ==============================="
+            ),
+            Some((12, 0))
+        );
+    }
+
+    #[test]
+    fn fpm_output_has_no_summary() {
+        let output = "\
+check.f90                              done.
+libdemo.a                              done.
+check                                  done.
+[100%] Project compiled successfully.
+ all tests passed
+<ERROR> Execution for object \" check \" returned exit code  1
+<ERROR> *cmd_run*:stopping due to failed executions
+STOP 1
+";
+        assert_eq!(count(output), None);
+    }
+
+    #[test]
+    fn other_runners_report_their_own_summaries() {
+        assert_eq!(
+            parse_test_summary("75% tests passed, 1 tests failed out of 4"),
+            Some((3, 1))
+        );
+        assert_eq!(
+            parse_test_summary("Tests:       1 failed, 3 passed, 4 total"),
+            Some((3, 1))
+        );
+        assert_eq!(count("# tests 4\n# pass 3\n# fail 1\n"), Some((3, 1)));
+        assert_eq!(
+            parse_test_summary(
+                "Build Summary: 3/5 steps succeeded; 1 failed; 4/5 tests passed; 1 failed"
+            ),
+            Some((4, 1))
+        );
+    }
 }